@@ -0,0 +1,153 @@
+//! False sharing и `CachePadded`.
+//!
+//! `counters` показывает эффект в чистом виде: два потока крутят каждый
+//! свой счётчик; без выравнивания счётчики лежат в одной кэш-линии.
+//!
+//! `spsc` — пропускная способность производитель/потребитель для `MSQueue`
+//! и `RingBuffer`, у которых `head`/`tail` (`write_index`/`read_index`)
+//! теперь в разных линиях. Для сравнения с невыровненной раскладкой
+//! сохраните базу на предыдущей ревизии (`cargo bench --bench cache_padding
+//! -- --save-baseline unpadded`) и запустите с `--baseline unpadded`.
+//!
+//! Эффект виден только на нескольких ядрах: на одном потоки не выполняются
+//! одновременно и линию никто не выбивает.
+
+use std::{
+    hint::black_box,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Barrier,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rust_lockfree::{
+    cache_padded::CachePadded, ms_queue_crossbeam::MSQueue, ring_buffer::RingBuffer,
+};
+
+const RING_CAPACITY: usize = 1024;
+
+/// Два счётчика рядом — в одной кэш-линии.
+#[derive(Default)]
+struct Adjacent {
+    a: AtomicUsize,
+    b: AtomicUsize,
+}
+
+/// Два счётчика в разных кэш-линиях.
+#[derive(Default)]
+struct Padded {
+    a: CachePadded<AtomicUsize>,
+    b: CachePadded<AtomicUsize>,
+}
+
+/// Запускает `left` и `right` одновременно и возвращает время более медленного.
+fn run_pair<S, L, R>(shared: Arc<S>, left: L, right: R) -> Duration
+where
+    S: Send + Sync + 'static,
+    L: FnOnce(&S) + Send + 'static,
+    R: FnOnce(&S) + Send + 'static,
+{
+    let barrier = Arc::new(Barrier::new(2));
+    let spawn = |f: Box<dyn FnOnce(&S) + Send>| {
+        let (shared, barrier) = (Arc::clone(&shared), Arc::clone(&barrier));
+        thread::spawn(move || {
+            barrier.wait();
+            let start = Instant::now();
+            f(&shared);
+            start.elapsed()
+        })
+    };
+    let handles = [spawn(Box::new(left)), spawn(Box::new(right))];
+    handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .max()
+        .unwrap()
+}
+
+fn bump(counter: &AtomicUsize, iters: u64) {
+    for _ in 0..iters {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn counters(c: &mut Criterion) {
+    let mut group = c.benchmark_group("counters");
+    group.throughput(Throughput::Elements(2));
+    group.bench_function("adjacent", |b| {
+        b.iter_custom(|iters| {
+            run_pair(
+                Arc::new(Adjacent::default()),
+                move |s: &Adjacent| bump(&s.a, iters),
+                move |s: &Adjacent| bump(&s.b, iters),
+            )
+        })
+    });
+    group.bench_function("padded", |b| {
+        b.iter_custom(|iters| {
+            run_pair(
+                Arc::new(Padded::default()),
+                move |s: &Padded| bump(&s.a, iters),
+                move |s: &Padded| bump(&s.b, iters),
+            )
+        })
+    });
+    group.finish();
+}
+
+fn spsc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spsc");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("ms_queue", |b| {
+        b.iter_custom(|iters| {
+            run_pair(
+                Arc::new(MSQueue::<u64>::new()),
+                move |q: &MSQueue<u64>| (0..iters).for_each(|i| q.push(i)),
+                move |q: &MSQueue<u64>| {
+                    let mut taken = 0;
+                    while taken < iters {
+                        if let Some(v) = q.pop() {
+                            black_box(v);
+                            taken += 1;
+                        }
+                    }
+                },
+            )
+        })
+    });
+    group.bench_function("ring_buffer", |b| {
+        b.iter_custom(|iters| {
+            run_pair(
+                Arc::new(RingBuffer::new(RING_CAPACITY)),
+                move |r: &RingBuffer<u64>| {
+                    for i in 0..iters {
+                        let mut v = i;
+                        while let Err(back) = r.push(v) {
+                            v = back;
+                            thread::yield_now();
+                        }
+                    }
+                },
+                move |r: &RingBuffer<u64>| {
+                    let mut taken = 0;
+                    while taken < iters {
+                        match r.pop() {
+                            Some(v) => {
+                                black_box(v);
+                                taken += 1;
+                            }
+                            None => thread::yield_now(),
+                        }
+                    }
+                },
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, counters, spsc);
+criterion_main!(benches);
@@ -0,0 +1,124 @@
+//! FaaQueue против MSQueue и RingBuffer при растущем числе потоков.
+//!
+//! Каждый поток выполняет пары push+pop над общей очередью; в пропускной
+//! способности учитывается одна пара на поток. MSQueue — в двух вариантах:
+//! на `crossbeam_epoch` и на собственном EBR (как и FaaQueue), чтобы разница
+//! не сводилась к схеме освобождения памяти.
+
+use std::{
+    hint::black_box,
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust_lockfree::{faa_queue::FaaQueue, ms_queue, ms_queue_crossbeam, ring_buffer::RingBuffer};
+
+/// Ёмкость RingBuffer: в парах push+pop в очереди не больше элемента на поток.
+const RING_CAPACITY: usize = 1024;
+
+/// Общий интерфейс очередей для бенчмарка.
+trait Queue: Send + Sync + 'static {
+    fn push(&self, value: u64);
+    fn pop(&self) -> Option<u64>;
+}
+
+impl Queue for FaaQueue<u64> {
+    fn push(&self, value: u64) {
+        FaaQueue::push(self, value)
+    }
+    fn pop(&self) -> Option<u64> {
+        FaaQueue::pop(self)
+    }
+}
+
+impl Queue for ms_queue::MSQueue<u64> {
+    fn push(&self, value: u64) {
+        ms_queue::MSQueue::push(self, value)
+    }
+    fn pop(&self) -> Option<u64> {
+        ms_queue::MSQueue::pop(self)
+    }
+}
+
+impl Queue for ms_queue_crossbeam::MSQueue<u64> {
+    fn push(&self, value: u64) {
+        ms_queue_crossbeam::MSQueue::push(self, value)
+    }
+    fn pop(&self) -> Option<u64> {
+        ms_queue_crossbeam::MSQueue::pop(self)
+    }
+}
+
+impl Queue for RingBuffer<u64> {
+    fn push(&self, value: u64) {
+        let mut value = value;
+        while let Err(back) = RingBuffer::push(self, value) {
+            value = back;
+            thread::yield_now();
+        }
+    }
+    fn pop(&self) -> Option<u64> {
+        RingBuffer::pop(self)
+    }
+}
+
+/// Запускает `threads` потоков по `iters` пар push+pop и возвращает
+/// время самого медленного из них.
+fn run_threads<Q: Queue>(queue: &Arc<Q>, threads: usize, iters: u64) -> Duration {
+    let barrier = Arc::new(Barrier::new(threads));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let (queue, barrier) = (Arc::clone(queue), Arc::clone(&barrier));
+            thread::spawn(move || {
+                barrier.wait();
+                let start = Instant::now();
+                for i in 0..iters {
+                    queue.push(i);
+                    black_box(queue.pop());
+                }
+                start.elapsed()
+            })
+        })
+        .collect();
+    handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .max()
+        .unwrap()
+}
+
+fn bench_queue<Q: Queue>(
+    group: &mut criterion::BenchmarkGroup<'_, criterion::measurement::WallTime>,
+    name: &str,
+    threads: usize,
+    new: impl Fn() -> Q,
+) {
+    group.bench_with_input(BenchmarkId::new(name, threads), &threads, |b, &t| {
+        let queue = Arc::new(new());
+        b.iter_custom(|iters| run_threads(&queue, t, iters))
+    });
+}
+
+fn contention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("queue_contention");
+    for threads in [1, 2, 4, 8, 16, 32] {
+        group.throughput(Throughput::Elements(threads as u64));
+        bench_queue(&mut group, "faa_queue", threads, FaaQueue::new);
+        bench_queue(&mut group, "ms_queue_ebr", threads, ms_queue::MSQueue::new);
+        bench_queue(
+            &mut group,
+            "ms_queue_crossbeam",
+            threads,
+            ms_queue_crossbeam::MSQueue::<u64>::new,
+        );
+        bench_queue(&mut group, "ring_buffer", threads, || {
+            RingBuffer::new(RING_CAPACITY)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, contention);
+criterion_main!(benches);
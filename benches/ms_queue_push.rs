@@ -0,0 +1,60 @@
+//! Пропускная способность push у MSQueue, когда никто не ждёт.
+//!
+//! Быстрый путь push после вставки только проверяет, есть ли ждущие
+//! (`pop_blocking`/`pop_async`); бенчмарк следит, чтобы эта проверка
+//! не замедляла push. Каждый замер — на свежей очереди, чтобы её рост
+//! между замерами не влиял на время.
+
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust_lockfree::{
+    ms_queue_crossbeam::MSQueue,
+    reclaim::{CrossbeamEpoch, Ebr, Reclaimer},
+};
+
+/// Запускает `threads` потоков по `iters` push и возвращает время самого
+/// медленного из них.
+fn run_threads<R: Reclaimer>(threads: usize, iters: u64) -> Duration {
+    let queue = Arc::new(MSQueue::<u64, R>::default());
+    let barrier = Arc::new(Barrier::new(threads));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let (queue, barrier) = (Arc::clone(&queue), Arc::clone(&barrier));
+            thread::spawn(move || {
+                barrier.wait();
+                let start = Instant::now();
+                for i in 0..iters {
+                    queue.push(i);
+                }
+                start.elapsed()
+            })
+        })
+        .collect();
+    handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .max()
+        .unwrap()
+}
+
+fn push(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ms_queue_push");
+    for threads in [1, 2, 4, 8] {
+        group.throughput(Throughput::Elements(threads as u64));
+        group.bench_with_input(BenchmarkId::new("crossbeam", threads), &threads, |b, &t| {
+            b.iter_custom(|iters| run_threads::<CrossbeamEpoch>(t, iters))
+        });
+        group.bench_with_input(BenchmarkId::new("ebr", threads), &threads, |b, &t| {
+            b.iter_custom(|iters| run_threads::<Ebr>(t, iters))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, push);
+criterion_main!(benches);
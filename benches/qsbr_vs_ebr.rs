@@ -0,0 +1,102 @@
+//! QSBR против EBR на нагрузке "много чтений, редкие записи".
+//!
+//! Каждый поток читает разделяемое значение и раз в `WRITE_EVERY` чтений
+//! подменяет его, откладывая старое. EBR закрепляется (`ebr::pin`) на
+//! каждое чтение, QSBR читает без охраны и объявляет точку покоя раз
+//! в `QUIESCENT_EVERY` чтений.
+
+use std::{
+    hint::black_box,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Arc, Barrier,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust_lockfree::{ebr, qsbr};
+
+const WRITE_EVERY: u64 = 1000;
+const QUIESCENT_EVERY: u64 = 100;
+
+/// Запускает `threads` потоков по `iters` чтений в каждом и возвращает
+/// время самого медленного из них.
+fn run_threads<F>(threads: usize, iters: u64, body: F) -> Duration
+where
+    F: Fn(&AtomicPtr<u64>, u64) + Send + Sync + 'static,
+{
+    let shared = Arc::new(AtomicPtr::new(Box::into_raw(Box::new(0u64))));
+    let body = Arc::new(body);
+    let barrier = Arc::new(Barrier::new(threads));
+
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let (shared, body, barrier) = (shared.clone(), body.clone(), barrier.clone());
+            thread::spawn(move || {
+                barrier.wait();
+                let start = Instant::now();
+                body(&shared, iters);
+                start.elapsed()
+            })
+        })
+        .collect();
+    let elapsed = handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .max()
+        .unwrap();
+
+    // Все потоки завершены — последнее значение никто не видит.
+    drop(unsafe { Box::from_raw(shared.load(Ordering::Relaxed)) });
+    elapsed
+}
+
+fn ebr_reads(shared: &AtomicPtr<u64>, iters: u64) {
+    for i in 0..iters {
+        let guard = ebr::pin();
+        let p = shared.load(Ordering::Acquire);
+        black_box(unsafe { *p });
+
+        if i % WRITE_EVERY == 0 {
+            let old = shared.swap(Box::into_raw(Box::new(i)), Ordering::AcqRel);
+            ebr::retire(old, |p| drop(unsafe { Box::from_raw(p) }), &guard);
+        }
+    }
+    ebr::flush();
+}
+
+fn qsbr_reads(shared: &AtomicPtr<u64>, iters: u64) {
+    for i in 0..iters {
+        let p = shared.load(Ordering::Acquire);
+        black_box(unsafe { *p });
+
+        if i % WRITE_EVERY == 0 {
+            let old = shared.swap(Box::into_raw(Box::new(i)), Ordering::AcqRel);
+            unsafe { qsbr::retire(old) };
+        }
+        if i % QUIESCENT_EVERY == 0 {
+            qsbr::quiescent();
+        }
+    }
+    // Поток завершается — больше не задерживаем остальных.
+    qsbr::offline();
+}
+
+fn read_heavy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_heavy");
+    for threads in [1, 2, 4, 8, 16, 32] {
+        group.throughput(Throughput::Elements(threads as u64));
+        group.bench_with_input(BenchmarkId::new("ebr", threads), &threads, |b, &t| {
+            b.iter_custom(|iters| run_threads(t, iters, ebr_reads))
+        });
+        group.bench_with_input(BenchmarkId::new("qsbr", threads), &threads, |b, &t| {
+            b.iter_custom(|iters| run_threads(t, iters, qsbr_reads))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, read_heavy);
+criterion_main!(benches);
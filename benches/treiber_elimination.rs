@@ -0,0 +1,86 @@
+//! TreiberStack против EliminationStack при растущей конкуренции.
+//!
+//! Каждый поток выполняет пары push+pop над общим стеком; в пропускной
+//! способности учитывается одна пара на поток.
+
+use std::{
+    hint::black_box,
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust_lockfree::treiber_stack::{EliminationStack, TreiberStack};
+
+/// Общий интерфейс двух стеков для бенчмарка.
+trait Stack: Send + Sync + 'static {
+    fn push(&self, value: u64);
+    fn pop(&self) -> Option<u64>;
+}
+
+impl Stack for TreiberStack<u64> {
+    fn push(&self, value: u64) {
+        TreiberStack::push(self, value)
+    }
+    fn pop(&self) -> Option<u64> {
+        TreiberStack::pop(self)
+    }
+}
+
+impl Stack for EliminationStack<u64> {
+    fn push(&self, value: u64) {
+        EliminationStack::push(self, value)
+    }
+    fn pop(&self) -> Option<u64> {
+        EliminationStack::pop(self)
+    }
+}
+
+/// Запускает `threads` потоков по `iters` пар push+pop и возвращает
+/// время самого медленного из них.
+fn run_threads<S: Stack>(stack: &Arc<S>, threads: usize, iters: u64) -> Duration {
+    let barrier = Arc::new(Barrier::new(threads));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let (stack, barrier) = (Arc::clone(stack), Arc::clone(&barrier));
+            thread::spawn(move || {
+                barrier.wait();
+                let start = Instant::now();
+                for i in 0..iters {
+                    stack.push(i);
+                    black_box(stack.pop());
+                }
+                start.elapsed()
+            })
+        })
+        .collect();
+    handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .max()
+        .unwrap()
+}
+
+fn contention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("treiber_contention");
+    for threads in [1, 2, 4, 8, 16, 32] {
+        group.throughput(Throughput::Elements(threads as u64));
+        group.bench_with_input(BenchmarkId::new("plain", threads), &threads, |b, &t| {
+            let stack = Arc::new(TreiberStack::new());
+            b.iter_custom(|iters| run_threads(&stack, t, iters))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("elimination", threads),
+            &threads,
+            |b, &t| {
+                let stack = Arc::new(EliminationStack::with_capacity(t / 2));
+                b.iter_custom(|iters| run_threads(&stack, t, iters))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, contention);
+criterion_main!(benches);
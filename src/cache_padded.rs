@@ -0,0 +1,106 @@
+//! Выравнивание по кэш-линии против false sharing.
+//!
+//! Если два часто изменяемых поля (например, `head` и `tail` очереди)
+//! лежат в одной кэш-линии, запись в одно из них выбивает линию из кэшей
+//! остальных ядер — и потоки, работающие с разными полями, мешают друг
+//! другу, хотя данных не разделяют. [`CachePadded`] выравнивает значение
+//! и дополняет его до целой линии, так что соседние поля никогда не делят её.
+//!
+//! Размер линии: на x86_64 и aarch64 берём 128 байт — процессоры там
+//! подкачивают линии парами (adjacent-line prefetch), так что 64 байт мало;
+//! на остальных платформах — 64.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// Значение `T`, выровненное и дополненное до размера кэш-линии.
+#[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64"), repr(align(128)))]
+#[cfg_attr(
+    not(any(target_arch = "x86_64", target_arch = "aarch64")),
+    repr(align(64))
+)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CachePadded<T> {
+    value: T,
+}
+
+impl<T> CachePadded<T> {
+    /// Оборачивает значение.
+    pub const fn new(value: T) -> Self {
+        CachePadded { value }
+    }
+
+    /// Возвращает значение.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T> From<T> for CachePadded<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CachePadded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachePadded")
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_layout() {
+        let align = mem::align_of::<CachePadded<u8>>();
+        assert!(align >= 64);
+        assert_eq!(mem::size_of::<CachePadded<u8>>(), align);
+        assert_eq!(mem::size_of::<CachePadded<[u8; 65]>>() % align, 0);
+    }
+
+    #[test]
+    fn test_neighbours_do_not_share_a_line() {
+        struct Pair {
+            a: CachePadded<AtomicUsize>,
+            b: CachePadded<AtomicUsize>,
+        }
+
+        let pair = Pair {
+            a: CachePadded::new(AtomicUsize::new(1)),
+            b: CachePadded::new(AtomicUsize::new(2)),
+        };
+        let a = &*pair.a as *const AtomicUsize as usize;
+        let b = &*pair.b as *const AtomicUsize as usize;
+        assert!(a.abs_diff(b) >= mem::align_of::<CachePadded<u8>>());
+    }
+
+    #[test]
+    fn test_deref() {
+        let mut x = CachePadded::new(vec![1]);
+        x.push(2);
+        assert_eq!(x.len(), 2);
+        assert_eq!(x.into_inner(), vec![1, 2]);
+    }
+}
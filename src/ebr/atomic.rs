@@ -0,0 +1,484 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use super::Guard;
+
+/// Общий интерфейс для указателей, которые можно записать в [`Atomic`]:
+/// [`Owned`] (передаём владение) и [`Shared`] (уже опубликованный объект).
+pub trait Pointer<T> {
+    /// Превращает указатель в сырой, не освобождая объект.
+    fn into_ptr(self) -> *mut T;
+
+    /// Восстанавливает указатель из сырого.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` должен быть получен из `into_ptr` того же типа.
+    unsafe fn from_ptr(ptr: *mut T) -> Self;
+}
+
+/// Атомарный указатель на объект в куче, защищаемый EBR.
+///
+/// Аналог `crossbeam_epoch::Atomic`: все загрузки делаются под `&'g Guard`,
+/// и полученный [`Shared<'g, T>`] не может пережить этот Guard.
+/// Сам `Atomic` объектом не владеет и при дропе ничего не освобождает.
+pub struct Atomic<T> {
+    data: AtomicPtr<T>,
+    _marker: PhantomData<*mut T>,
+}
+
+unsafe impl<T: Send + Sync> Send for Atomic<T> {}
+unsafe impl<T: Send + Sync> Sync for Atomic<T> {}
+
+impl<T> Atomic<T> {
+    /// Нулевой указатель.
+    pub const fn null() -> Self {
+        Atomic {
+            data: AtomicPtr::new(ptr::null_mut()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Размещает `value` в куче и указывает на него.
+    pub fn new(value: T) -> Self {
+        Self::from(Owned::new(value))
+    }
+
+    /// Загружает указатель. Результат живёт не дольше `guard`.
+    pub fn load<'g>(&self, ord: Ordering, _guard: &'g Guard<'_>) -> Shared<'g, T> {
+        unsafe { Shared::from_ptr(self.data.load(ord)) }
+    }
+
+    /// Записывает новый указатель (старый не освобождается).
+    pub fn store<P: Pointer<T>>(&self, new: P, ord: Ordering) {
+        self.data.store(new.into_ptr(), ord);
+    }
+
+    /// Записывает новый указатель и возвращает предыдущий.
+    pub fn swap<'g, P: Pointer<T>>(
+        &self,
+        new: P,
+        ord: Ordering,
+        _guard: &'g Guard<'_>,
+    ) -> Shared<'g, T> {
+        unsafe { Shared::from_ptr(self.data.swap(new.into_ptr(), ord)) }
+    }
+
+    /// Атомарно заменяет `current` на `new`.
+    ///
+    /// При неудаче `new` возвращается обратно вместе с актуальным значением,
+    /// поэтому [`Owned`] не теряется и его можно использовать в следующей попытке.
+    pub fn compare_exchange<'g, P: Pointer<T>>(
+        &self,
+        current: Shared<'_, T>,
+        new: P,
+        success: Ordering,
+        failure: Ordering,
+        _guard: &'g Guard<'_>,
+    ) -> Result<Shared<'g, T>, CompareExchangeError<'g, T, P>> {
+        let new = new.into_ptr();
+        self.data
+            .compare_exchange(current.as_raw() as *mut T, new, success, failure)
+            .map(|_| unsafe { Shared::from_ptr(new) })
+            .map_err(|actual| unsafe {
+                CompareExchangeError {
+                    current: Shared::from_ptr(actual),
+                    new: P::from_ptr(new),
+                }
+            })
+    }
+
+    /// Как [`Atomic::compare_exchange`], но может ложно не сработать.
+    pub fn compare_exchange_weak<'g, P: Pointer<T>>(
+        &self,
+        current: Shared<'_, T>,
+        new: P,
+        success: Ordering,
+        failure: Ordering,
+        _guard: &'g Guard<'_>,
+    ) -> Result<Shared<'g, T>, CompareExchangeError<'g, T, P>> {
+        let new = new.into_ptr();
+        self.data
+            .compare_exchange_weak(current.as_raw() as *mut T, new, success, failure)
+            .map(|_| unsafe { Shared::from_ptr(new) })
+            .map_err(|actual| unsafe {
+                CompareExchangeError {
+                    current: Shared::from_ptr(actual),
+                    new: P::from_ptr(new),
+                }
+            })
+    }
+
+    /// Забирает объект во владение без Guard.
+    ///
+    /// # Safety
+    ///
+    /// Вызывающий гарантирует, что к `Atomic` больше никто не обращается
+    /// (например, внутри `Drop` при `&mut self`) и указатель не нулевой.
+    pub unsafe fn into_owned(self) -> Owned<T> {
+        Owned::from_ptr(self.data.into_inner())
+    }
+
+    /// Загружает указатель без Guard, когда доступ к `Atomic` эксклюзивен.
+    ///
+    /// # Safety
+    ///
+    /// Как и у [`Atomic::into_owned`]: параллельных изменений быть не должно.
+    pub unsafe fn load_unprotected<'g>(&self, ord: Ordering) -> Shared<'g, T> {
+        Shared::from_ptr(self.data.load(ord))
+    }
+}
+
+impl<T> Default for Atomic<T> {
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<T> From<Owned<T>> for Atomic<T> {
+    fn from(owned: Owned<T>) -> Self {
+        Atomic {
+            data: AtomicPtr::new(owned.into_ptr()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> From<Shared<'_, T>> for Atomic<T> {
+    fn from(shared: Shared<'_, T>) -> Self {
+        Atomic {
+            data: AtomicPtr::new(shared.into_ptr()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Atomic<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Atomic")
+            .field(&self.data.load(Ordering::SeqCst))
+            .finish()
+    }
+}
+
+/// Ошибка `compare_exchange`: актуальное значение и возвращённый `new`.
+pub struct CompareExchangeError<'g, T, P: Pointer<T>> {
+    /// Значение, которое лежало в `Atomic` на момент сравнения.
+    pub current: Shared<'g, T>,
+    /// Указатель, который мы пытались записать.
+    pub new: P,
+}
+
+impl<T, P: Pointer<T> + fmt::Debug> fmt::Debug for CompareExchangeError<'_, T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompareExchangeError")
+            .field("current", &self.current)
+            .field("new", &self.new)
+            .finish()
+    }
+}
+
+/// Владеющий указатель на объект в куче (аналог `Box<T>`),
+/// который ещё не опубликован в разделяемой структуре.
+pub struct Owned<T> {
+    data: *mut T,
+    _marker: PhantomData<Box<T>>,
+}
+
+unsafe impl<T: Send> Send for Owned<T> {}
+unsafe impl<T: Sync> Sync for Owned<T> {}
+
+impl<T> Owned<T> {
+    /// Размещает `value` в куче.
+    pub fn new(value: T) -> Self {
+        Self::from(Box::new(value))
+    }
+
+    /// Публикует объект: дальше им управляет EBR, а не `Owned`.
+    pub fn into_shared<'g>(self, _guard: &'g Guard<'_>) -> Shared<'g, T> {
+        unsafe { Shared::from_ptr(self.into_ptr()) }
+    }
+
+    /// Превращает обратно в `Box<T>`.
+    pub fn into_box(self) -> Box<T> {
+        unsafe { Box::from_raw(self.into_ptr()) }
+    }
+}
+
+impl<T> Pointer<T> for Owned<T> {
+    fn into_ptr(self) -> *mut T {
+        let ptr = self.data;
+        mem::forget(self);
+        ptr
+    }
+
+    unsafe fn from_ptr(ptr: *mut T) -> Self {
+        debug_assert!(!ptr.is_null(), "Owned не может быть нулевым");
+        Owned {
+            data: ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> From<Box<T>> for Owned<T> {
+    fn from(b: Box<T>) -> Self {
+        unsafe { Self::from_ptr(Box::into_raw(b)) }
+    }
+}
+
+impl<T> Drop for Owned<T> {
+    fn drop(&mut self) {
+        unsafe { drop(Box::from_raw(self.data)) }
+    }
+}
+
+impl<T> Deref for Owned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.data }
+    }
+}
+
+impl<T> DerefMut for Owned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.data }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Owned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Owned").field(&**self).finish()
+    }
+}
+
+/// Указатель на разделяемый объект, действительный, пока жив Guard `'g`.
+///
+/// Копируется свободно; разыменование `unsafe`, так как объект могли
+/// уже отцепить и отложить на освобождение (он всё ещё жив до конца `'g`,
+/// но указатель может быть и нулевым, и чужим).
+pub struct Shared<'g, T> {
+    data: *const T,
+    _marker: PhantomData<(&'g (), *const T)>,
+}
+
+impl<T> Clone for Shared<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Shared<'_, T> {}
+
+impl<'g, T> Shared<'g, T> {
+    /// Нулевой указатель.
+    pub const fn null() -> Self {
+        Shared {
+            data: ptr::null(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Равен ли указатель null.
+    pub fn is_null(&self) -> bool {
+        self.data.is_null()
+    }
+
+    /// Сырой указатель.
+    pub fn as_raw(&self) -> *const T {
+        self.data
+    }
+
+    /// Разыменовывает указатель.
+    ///
+    /// # Safety
+    ///
+    /// Указатель не нулевой и был загружен из `Atomic` под тем же Guard
+    /// (или объект иначе гарантированно жив).
+    pub unsafe fn deref(&self) -> &'g T {
+        &*self.data
+    }
+
+    /// Как [`Shared::deref`], но возвращает `None` для нулевого указателя.
+    ///
+    /// # Safety
+    ///
+    /// См. [`Shared::deref`].
+    pub unsafe fn as_ref(&self) -> Option<&'g T> {
+        self.data.as_ref()
+    }
+
+    /// Забирает объект во владение.
+    ///
+    /// # Safety
+    ///
+    /// Объект больше недостижим для других потоков и не был отложен
+    /// на освобождение.
+    pub unsafe fn into_owned(self) -> Owned<T> {
+        Owned::from_ptr(self.data as *mut T)
+    }
+}
+
+impl<T> Pointer<T> for Shared<'_, T> {
+    fn into_ptr(self) -> *mut T {
+        self.data as *mut T
+    }
+
+    unsafe fn from_ptr(ptr: *mut T) -> Self {
+        Shared {
+            data: ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for Shared<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<T> Eq for Shared<'_, T> {}
+
+impl<T> fmt::Debug for Shared<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Shared").field(&self.data).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ebr::Collector;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::thread;
+
+    /// Значение, считающее свои дропы.
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_load_store() {
+        let collector = Collector::new();
+        let handle = collector.register();
+        let guard = &handle.pin();
+
+        let a = Atomic::new(1);
+        assert_eq!(unsafe { *a.load(Ordering::Acquire, guard).deref() }, 1);
+
+        let old = a.swap(Owned::new(2), Ordering::AcqRel, guard);
+        assert_eq!(unsafe { *a.load(Ordering::Acquire, guard).deref() }, 2);
+        unsafe {
+            drop(old.into_owned());
+            drop(a.into_owned());
+        }
+
+        let n: Atomic<i32> = Atomic::null();
+        assert!(n.load(Ordering::Acquire, guard).is_null());
+        assert!(unsafe { n.load(Ordering::Acquire, guard).as_ref() }.is_none());
+    }
+
+    #[test]
+    fn test_compare_exchange_returns_new_on_failure() {
+        let collector = Collector::new();
+        let handle = collector.register();
+        let guard = &handle.pin();
+
+        let a = Atomic::new(1);
+        let cur = a.load(Ordering::Acquire, guard);
+
+        // Неверное ожидаемое значение => Owned возвращается обратно
+        let err = a
+            .compare_exchange(
+                Shared::null(),
+                Owned::new(2),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                guard,
+            )
+            .unwrap_err();
+        assert_eq!(err.current, cur);
+        assert_eq!(*err.new, 2);
+
+        // Повторяем с тем же Owned и верным ожиданием
+        let new = a
+            .compare_exchange(cur, err.new, Ordering::AcqRel, Ordering::Acquire, guard)
+            .unwrap();
+        assert_eq!(unsafe { *new.deref() }, 2);
+        unsafe {
+            drop(cur.into_owned());
+            drop(a.into_owned());
+        }
+    }
+
+    #[test]
+    fn test_defer_destroy_frees_once() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let handle = collector.register();
+        let count = 100;
+
+        for _ in 0..count {
+            let guard = &handle.pin();
+            let a = Atomic::new(DropCounter(drops.clone()));
+            let old = a.swap(Shared::null(), Ordering::AcqRel, guard);
+            unsafe { guard.defer_destroy(old) };
+        }
+        assert!(drops.load(Ordering::Relaxed) < count);
+
+        // Оставшийся мусор уходит сиротам и подбирается следующим участником
+        drop(handle);
+        let handle = collector.register();
+        for _ in 0..200 {
+            let guard = &handle.pin();
+            unsafe { guard.defer_destroy(Owned::new(0u8).into_shared(guard)) };
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), count);
+    }
+
+    #[test]
+    fn test_concurrent_swap() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let a = Arc::new(Atomic::new(DropCounter(drops.clone())));
+        let threads = 4;
+        let iters = 500;
+
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let (a, c, drops) = (a.clone(), collector.clone(), drops.clone());
+                thread::spawn(move || {
+                    let handle = c.register();
+                    for _ in 0..iters {
+                        let guard = &handle.pin();
+                        let new = Owned::new(DropCounter(drops.clone()));
+                        let old = a.swap(new, Ordering::AcqRel, guard);
+                        // Объект под Guard ещё жив, даже если его отложили
+                        let _ = unsafe { old.deref() };
+                        unsafe { guard.defer_destroy(old) };
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        let a = Arc::try_unwrap(a).unwrap();
+        drop(unsafe { a.into_owned() });
+        assert!(drops.load(Ordering::Relaxed) <= threads * iters + 1);
+    }
+}
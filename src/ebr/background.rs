@@ -0,0 +1,230 @@
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use super::{Collector, Stats};
+
+/// Обработчик периодических снимков статистики.
+type StatsHook = Box<dyn Fn(&Stats) + Send>;
+
+/// Фоновый поток очистки для одного [`Collector`].
+///
+/// Раз в `interval` пробует продвинуть эпоху и освобождает глобальный мусор.
+/// Пока поток работает, участники при последнем unpin сбрасывают свой
+/// локальный мусор в глобальный, поэтому поток, который отложил несколько
+/// объектов и "уснул", больше не держит их вечно.
+///
+/// Через [`BackgroundReclaimer::start_with_stats`] можно получать снимок
+/// [`Stats`] после каждой итерации (например, чтобы выгружать метрики).
+///
+/// Поток останавливается через [`BackgroundReclaimer::stop`] или при дропе.
+pub struct BackgroundReclaimer {
+    collector: Collector,
+    interval: Duration,
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BackgroundReclaimer {
+    /// Запускает фоновый поток для `collector`.
+    pub fn start(collector: &Collector, interval: Duration) -> Self {
+        Self::spawn(collector, interval, None)
+    }
+
+    /// Как [`BackgroundReclaimer::start`], но после каждой итерации
+    /// передаёт снимок статистики в `hook`.
+    pub fn start_with_stats<F>(collector: &Collector, interval: Duration, hook: F) -> Self
+    where
+        F: Fn(&Stats) + Send + 'static,
+    {
+        Self::spawn(collector, interval, Some(Box::new(hook)))
+    }
+
+    fn spawn(collector: &Collector, interval: Duration, hook: Option<StatsHook>) -> Self {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        collector.set_background(true);
+
+        let thread = {
+            let collector = collector.clone();
+            let stop = Arc::clone(&stop);
+            thread::Builder::new()
+                .name("ebr-reclaimer".into())
+                .spawn(move || run(&collector, interval, &stop, hook))
+                .expect("failed to spawn ebr reclaimer thread")
+        };
+
+        BackgroundReclaimer {
+            collector: collector.clone(),
+            interval,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Период между попытками очистки.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Коллектор, который обслуживает поток.
+    pub fn collector(&self) -> &Collector {
+        &self.collector
+    }
+
+    /// Останавливает поток и дожидается его завершения.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+
+        let (lock, cvar) = &*self.stop;
+        *lock.lock().unwrap() = true;
+        cvar.notify_one();
+        thread.join().expect("ebr reclaimer thread panicked");
+
+        self.collector.set_background(false);
+    }
+}
+
+impl Drop for BackgroundReclaimer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Основной цикл фонового потока: очистка, поиск зависших читателей,
+/// статистика, затем сон до `interval` (или до сигнала остановки).
+fn run(
+    collector: &Collector,
+    interval: Duration,
+    stop: &(Mutex<bool>, Condvar),
+    hook: Option<StatsHook>,
+) {
+    let (lock, cvar) = stop;
+    let mut stopped = lock.lock().unwrap();
+    while !*stopped {
+        drop(stopped);
+        collector.collect_global();
+        // Зависание по времени само не проявится, пока никто не пытается
+        // продвинуть эпоху, поэтому проверяем его здесь.
+        if collector.config().detects_stalls() {
+            collector.detect_stalls();
+        }
+        if let Some(hook) = &hook {
+            hook(&collector.stats());
+        }
+
+        stopped = lock.lock().unwrap();
+        if *stopped {
+            break;
+        }
+        stopped = cvar.wait_timeout(stopped, interval).unwrap().0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    /// Ждём, пока `f()` не станет true (но не дольше секунды).
+    fn wait_until(f: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if f() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        f()
+    }
+
+    #[test]
+    fn test_idle_thread_garbage_is_freed() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let reclaimer = BackgroundReclaimer::start(&collector, Duration::from_millis(1));
+        assert_eq!(reclaimer.interval(), Duration::from_millis(1));
+
+        // Откладываем несколько объектов (меньше порога) и "засыпаем",
+        // не отпуская дескриптор.
+        let handle = collector.register();
+        for _ in 0..5 {
+            let freed = freed.clone();
+            handle.pin().defer(move || {
+                freed.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        assert!(wait_until(|| freed.load(Ordering::Relaxed) == 5));
+        reclaimer.stop();
+    }
+
+    #[test]
+    fn test_pinned_reader_is_respected() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let reader = collector.register();
+        let writer = collector.register();
+        let _reclaimer = BackgroundReclaimer::start(&collector, Duration::from_millis(1));
+
+        let guard = reader.pin();
+        {
+            let freed = freed.clone();
+            writer.pin().defer(move || {
+                freed.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        // Пока читатель закреплён, объект освобождать нельзя
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+
+        drop(guard);
+        assert!(wait_until(|| freed.load(Ordering::Relaxed) == 1));
+    }
+
+    #[test]
+    fn test_stats_hook() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let collector = Collector::new();
+        let _handle = collector.register();
+
+        let r = reports.clone();
+        let reclaimer =
+            BackgroundReclaimer::start_with_stats(&collector, Duration::from_millis(1), move |s| {
+                r.lock().unwrap().push(s.clone());
+            });
+        assert!(wait_until(|| reports.lock().unwrap().len() >= 3));
+        reclaimer.stop();
+
+        let reports = reports.lock().unwrap();
+        assert!(reports.iter().all(|s| s.registered == 1));
+        assert!(reports.windows(2).all(|w| w[0].epoch <= w[1].epoch));
+    }
+
+    #[test]
+    fn test_stop_disables_flush() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        BackgroundReclaimer::start(&collector, Duration::from_millis(1)).stop();
+
+        // После остановки мусор снова остаётся локальным до порога
+        let handle = collector.register();
+        let f = freed.clone();
+        handle.pin().defer(move || {
+            f.fetch_add(1, Ordering::Relaxed);
+        });
+        collector.collect_global();
+        collector.collect_global();
+        collector.collect_global();
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+    }
+}
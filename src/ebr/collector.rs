@@ -0,0 +1,1194 @@
+use std::{
+    cell::{Cell, UnsafeCell},
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{self, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{
+    atomic::Shared,
+    deferred::Deferred,
+    epoch::{AtomicEpoch, Epoch},
+    stall::{StallHandler, StallState},
+    BackgroundReclaimer, Config, ParticipantStats, StallReport, Stats,
+};
+use crate::registry::{Registry, MAX_THREADS};
+
+/// Разделяемое состояние одного коллектора:
+/// 1) global_epoch — текущее значение "эпохи".
+/// 2) threads — реестр (до `MAX_THREADS` слотов) участников.
+/// 3) epoch_lock — мьютекс для управления продвижением эпохи.
+/// 4) global_garbage — мусор, оставшийся от отключившихся участников,
+///    и локальный мусор, сброшенный для фонового потока.
+/// 5) background — сколько фоновых потоков очистки сейчас запущено.
+/// 6) config — пороги сборки мусора.
+/// 7) reclaimed/reclaimed_bytes — сколько всего освобождено (для статистики).
+/// 8) created/stall_handler — отсчёт времени и обработчик зависших читателей.
+struct Global {
+    global_epoch: AtomicEpoch,
+    threads: Registry<Participant>,
+    epoch_lock: Mutex<()>,
+    global_garbage: Mutex<Vec<Retired>>,
+    background: AtomicUsize,
+    config: Config,
+    reclaimed: AtomicUsize,
+    reclaimed_bytes: AtomicUsize,
+    created: Instant,
+    stall_handler: RwLock<Option<StallHandler>>,
+}
+
+impl Global {
+    /// Пробует продвинуть глобальную эпоху:
+    /// 1) Берём epoch_lock.
+    /// 2) Проверяем, нет ли участника, который застрял на меньшей эпохе.
+    ///    - Если есть, возвращаем Err(номер его слота).
+    ///    - `caller` (слот участника, который сам пытается продвинуть эпоху)
+    ///      тоже мешает, но зависшим не считается: его Guard живёт на время
+    ///      этого вызова. Если мешает только он, возвращаем Err(None).
+    /// 3) Иначе увеличиваем global_epoch и возвращаем новое значение.
+    fn try_advance(&self, caller: Option<usize>) -> Result<Epoch, Option<usize>> {
+        let _lock = self.epoch_lock.lock().unwrap();
+        atomic::fence(Ordering::SeqCst);
+
+        let cur_epoch = self.global_epoch.load(Ordering::Relaxed);
+
+        // Если кто-то pinned на старой эпохе, выходим.
+        // Закреплённый участник не может опередить глобальную эпоху, поэтому
+        // "старая" == "не равна текущей" — без сравнения `<`, которое
+        // ломается при переполнении счётчика.
+        let mut blocked_by_caller = false;
+        for (index, thr) in self.threads.active() {
+            let le = thr.local_epoch.load(Ordering::Acquire);
+            if le.is_pinned() && le.unpinned() != cur_epoch {
+                // Кто-то ещё держит старую эпоху => нельзя освобождать
+                if caller == Some(index) {
+                    blocked_by_caller = true;
+                    continue;
+                }
+                thr.stall.note_blocked();
+                return Err(Some(index));
+            }
+        }
+        if blocked_by_caller {
+            return Err(None);
+        }
+
+        // Если все на cur_epoch => можно сдвинуть
+        let new_epoch = cur_epoch.successor();
+        self.global_epoch.store(new_epoch, Ordering::Release);
+        Ok(new_epoch)
+    }
+
+    /// Наносекунды с момента создания коллектора.
+    fn now_nanos(&self) -> u64 {
+        self.created.elapsed().as_nanos() as u64
+    }
+
+    /// Проверяет, не завис ли участник в слоте `index`. Отчёт выдаётся
+    /// один раз на каждое закрепление.
+    fn check_stall(&self, index: usize) -> Option<StallReport> {
+        let config = &self.config;
+        let thr = self.threads.get(index);
+        if !config.detects_stalls() || !self.threads.is_active(index) {
+            return None;
+        }
+        let pinned_epoch = thr.local_epoch.load(Ordering::Acquire);
+        if !pinned_epoch.is_pinned() {
+            return None;
+        }
+
+        let blocked_advances = thr.stall.blocked();
+        let pinned_for = thr.stall.pinned_for(self.now_nanos());
+        let stalled = config.stall_epochs.is_some_and(|n| blocked_advances >= n)
+            || config
+                .stall_timeout
+                .zip(pinned_for)
+                .is_some_and(|(limit, t)| t >= limit);
+        if !stalled || !thr.stall.mark_reported() {
+            return None;
+        }
+
+        Some(StallReport {
+            index,
+            pinned_epoch: pinned_epoch.value(),
+            global_epoch: self.global_epoch.load(Ordering::Acquire).value(),
+            blocked_advances,
+            pinned_for,
+            backtrace: thr.stall.backtrace(),
+        })
+    }
+
+    /// Передаёт отчёты обработчику (если он задан).
+    fn report_stalls(&self, reports: &[StallReport]) {
+        if reports.is_empty() {
+            return;
+        }
+        let handler = self.stall_handler.read().unwrap().clone();
+        if let Some(handler) = handler {
+            for report in reports {
+                handler(report);
+            }
+        }
+    }
+
+    /// Вызывается после неудачной попытки продвинуть эпоху из-за слота `index`.
+    fn on_blocked(&self, index: usize) {
+        if let Some(report) = self.check_stall(index) {
+            self.report_stalls(&[report]);
+        }
+    }
+
+    /// Выполняет отложенные функции и учитывает их в статистике.
+    fn reclaim(&self, ready: Vec<Retired>) {
+        if ready.is_empty() {
+            return;
+        }
+        let count = ready.len();
+        let bytes = ready.iter().map(|r| r.size).sum::<usize>();
+        for r in ready {
+            r.call();
+        }
+        self.reclaimed.fetch_add(count, Ordering::Relaxed);
+        self.reclaimed_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Переносит в `ready` глобальный мусор, чья эпоха истекла.
+    fn collect_global(&self, epoch: Epoch, ready: &mut Vec<Retired>) {
+        let mut garbage = self.global_garbage.lock().unwrap();
+        let mut i = 0;
+        while i < garbage.len() {
+            if garbage[i].is_expired(epoch) {
+                ready.push(garbage.swap_remove(i));
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+impl Drop for Global {
+    fn drop(&mut self) {
+        // Последний Collector ушёл => участников не осталось (каждый Local
+        // держит клон Collector), значит весь мусор можно освободить сразу.
+        for r in self.global_garbage.get_mut().unwrap().drain(..) {
+            r.call();
+        }
+    }
+}
+
+/// Состояние участника в слоте реестра:
+/// - local_epoch — эпоха, на которую участник "закрепился" при pin()
+///   (с флагом `is_pinned`; без флага — участник не в критической секции).
+/// - pending/pending_bytes — размер локальной корзины; пишет только
+///   владелец, читают все (для статистики).
+/// - stall — данные для обнаружения зависших читателей.
+struct Participant {
+    local_epoch: AtomicEpoch,
+    pending: AtomicUsize,
+    pending_bytes: AtomicUsize,
+    stall: StallState,
+}
+
+/// Данные, которые мы "откладываем" (retire) для отложенного вызова:
+/// - deferred: функция освобождения (или любая другая отложенная работа),
+/// - epoch: глобальная эпоха в момент retire,
+/// - size: подсказка о размере освобождаемой памяти в байтах.
+pub(crate) struct Retired {
+    deferred: Deferred,
+    epoch: Epoch,
+    size: usize,
+}
+
+// Retired переезжает в `global_garbage` и вызывается другим потоком.
+// Публичный `Guard::defer` требует `Send`, а для `defer_unchecked`
+// это гарантирует вызывающий.
+unsafe impl Send for Retired {}
+
+impl Retired {
+    /// Можно ли освободить объект при глобальной эпохе `global_epoch`.
+    /// Все, кто мог видеть объект, закреплены на эпохе <= `self.epoch`,
+    /// а продвижение до `epoch + 2` гарантирует, что все они уже ушли.
+    fn is_expired(&self, global_epoch: Epoch) -> bool {
+        global_epoch.wrapping_sub(self.epoch) >= 2
+    }
+
+    fn call(self) {
+        self.deferred.call();
+    }
+}
+
+/// Коллектор мусора на основе эпох (EBR).
+///
+/// Владеет собственной глобальной эпохой и реестром участников, поэтому
+/// независимые структуры данных (и тесты) не мешают друг другу.
+/// Клонирование дешёвое: клоны ссылаются на один и тот же коллектор.
+#[derive(Clone)]
+pub struct Collector {
+    global: Arc<Global>,
+}
+
+impl Default for Collector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Collector {
+    /// Создаёт новый коллектор с эпохой 0 и пустым реестром.
+    pub fn new() -> Self {
+        Self::with_config(Config::default())
+    }
+
+    /// Создаёт коллектор с заданными порогами сборки мусора.
+    pub fn with_config(config: Config) -> Self {
+        Self::with_epoch(config, Epoch::starting())
+    }
+
+    /// Коллектор, стартующий с произвольной эпохи (в тестах — у точки переполнения).
+    fn with_epoch(config: Config, epoch: Epoch) -> Self {
+        Collector {
+            global: Arc::new(Global {
+                global_epoch: AtomicEpoch::new(epoch),
+                threads: Registry::new(|| Participant {
+                    local_epoch: AtomicEpoch::new(Epoch::starting()),
+                    pending: AtomicUsize::new(0),
+                    pending_bytes: AtomicUsize::new(0),
+                    stall: StallState::default(),
+                }),
+                epoch_lock: Mutex::new(()),
+                global_garbage: Mutex::new(Vec::new()),
+                background: AtomicUsize::new(0),
+                config,
+                reclaimed: AtomicUsize::new(0),
+                reclaimed_bytes: AtomicUsize::new(0),
+                created: Instant::now(),
+                stall_handler: RwLock::new(None),
+            }),
+        }
+    }
+
+    /// Регистрирует нового участника: ищет свободный слот и активирует его.
+    ///
+    /// # Panics
+    ///
+    /// Если все `MAX_THREADS` слотов заняты.
+    pub fn register(&self) -> LocalHandle {
+        let index = self.global.threads.acquire("EBR threads");
+        let thr = self.global.threads.get(index);
+        thr.local_epoch.store(Epoch::starting(), Ordering::Relaxed);
+        thr.pending.store(0, Ordering::Relaxed);
+        thr.pending_bytes.store(0, Ordering::Relaxed);
+
+        let local = Box::new(Local {
+            index,
+            collector: self.clone(),
+            garbage: UnsafeCell::new(VecDeque::new()),
+            pin_count: Cell::new(0),
+            guard_count: Cell::new(0),
+            handle_count: Cell::new(1),
+            epoch: Cell::new(Epoch::starting()),
+        });
+        LocalHandle {
+            local: Box::into_raw(local),
+        }
+    }
+
+    /// Текущая глобальная эпоха.
+    ///
+    /// Счётчик растёт по модулю (`usize::MAX >> 1`), поэтому сравнивать эпохи
+    /// через `<` можно только на коротких дистанциях и вдали от переполнения.
+    pub fn epoch(&self) -> usize {
+        self.global.global_epoch.load(Ordering::Acquire).value()
+    }
+
+    /// Настройки коллектора.
+    pub fn config(&self) -> &Config {
+        &self.global.config
+    }
+
+    /// Запускает фоновый поток очистки с периодом `interval`.
+    pub fn start_background(&self, interval: Duration) -> BackgroundReclaimer {
+        BackgroundReclaimer::start(self, interval)
+    }
+
+    /// Одна итерация фоновой очистки: пробуем продвинуть эпоху
+    /// и освобождаем истёкший глобальный мусор.
+    pub(crate) fn collect_global(&self) {
+        let global = &self.global;
+        if let Err(Some(index)) = global.try_advance(None) {
+            global.on_blocked(index);
+        }
+
+        let epoch = global.global_epoch.load(Ordering::Acquire);
+        let mut ready = Vec::new();
+        global.collect_global(epoch, &mut ready);
+        global.reclaim(ready);
+    }
+
+    /// Задаёт обработчик зависших читателей.
+    ///
+    /// Читатель считается зависшим по порогам `Config::stall_epochs` и
+    /// `Config::stall_timeout`. Проверка делается при неудачной попытке
+    /// продвинуть эпоху и в [`Collector::detect_stalls`] (её вызывает фоновый
+    /// поток). Обработчик вызывается один раз на каждое закрепление.
+    pub fn on_stall<F>(&self, handler: F)
+    where
+        F: Fn(&StallReport) + Send + Sync + 'static,
+    {
+        *self.global.stall_handler.write().unwrap() = Some(Arc::new(handler));
+    }
+
+    /// Проверяет всех закреплённых участников на зависание, передаёт новые
+    /// отчёты обработчику и возвращает их.
+    pub fn detect_stalls(&self) -> Vec<StallReport> {
+        let global = &self.global;
+        let reports: Vec<_> = (0..MAX_THREADS)
+            .filter_map(|index| global.check_stall(index))
+            .collect();
+        global.report_stalls(&reports);
+        reports
+    }
+
+    /// Снимок состояния коллектора: эпоха, участники, объём мусора.
+    pub fn stats(&self) -> Stats {
+        let global = &self.global;
+        let cur_epoch = global.global_epoch.load(Ordering::Acquire);
+        let mut oldest: Option<Epoch> = None;
+        let mut stats = Stats {
+            epoch: cur_epoch.value(),
+            reclaimed: global.reclaimed.load(Ordering::Relaxed),
+            reclaimed_bytes: global.reclaimed_bytes.load(Ordering::Relaxed),
+            ..Stats::default()
+        };
+
+        for (index, thr) in global.threads.active() {
+            let le = thr.local_epoch.load(Ordering::Acquire);
+
+            stats.registered += 1;
+            if le.is_pinned() {
+                stats.pinned += 1;
+                // "Старше" == дальше от текущей эпохи (по модулю)
+                let e = le.unpinned();
+                if oldest.is_none_or(|o| cur_epoch.wrapping_sub(e) > cur_epoch.wrapping_sub(o)) {
+                    oldest = Some(e);
+                }
+            }
+            stats.participants.push(ParticipantStats {
+                index,
+                pinned_epoch: le.is_pinned().then(|| le.value()),
+                pending: thr.pending.load(Ordering::Relaxed),
+                pending_bytes: thr.pending_bytes.load(Ordering::Relaxed),
+            });
+        }
+
+        stats.oldest_pinned_epoch = oldest.map(Epoch::value);
+
+        {
+            let garbage = global.global_garbage.lock().unwrap();
+            stats.global_pending = garbage.len();
+            stats.global_pending_bytes = garbage.iter().map(|r| r.size).sum();
+        }
+        stats
+    }
+
+    /// Отмечает запуск (`true`) или остановку (`false`) фонового потока.
+    /// Пока хоть один фоновый поток работает, участники при unpin сбрасывают
+    /// свой мусор в глобальный, чтобы он не застревал у простаивающих потоков.
+    pub(crate) fn set_background(&self, running: bool) {
+        if running {
+            self.global.background.fetch_add(1, Ordering::SeqCst);
+        } else {
+            self.global.background.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl PartialEq for Collector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.global, &other.global)
+    }
+}
+
+impl Eq for Collector {}
+
+impl fmt::Debug for Collector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Collector")
+            .field("epoch", &self.epoch())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Локальное состояние участника. Живёт в куче, пока на него ссылается
+/// хотя бы один `LocalHandle` или `Guard` (счётчики handle_count/guard_count).
+pub(crate) struct Local {
+    index: usize,
+    collector: Collector,
+    garbage: UnsafeCell<VecDeque<Retired>>,
+    pin_count: Cell<usize>,
+    guard_count: Cell<usize>,
+    handle_count: Cell<usize>,
+    epoch: Cell<Epoch>,
+}
+
+impl Local {
+    fn global(&self) -> &Global {
+        &self.collector.global
+    }
+
+    fn participant(&self) -> &Participant {
+        self.global().threads.get(self.index)
+    }
+
+    /// pin():
+    /// 1) Если участник уже закреплён (вложенный Guard), просто увеличиваем счётчик.
+    /// 2) Иначе считываем global_epoch и записываем его в local_epoch.
+    /// 3) SeqCst-барьер: запись local_epoch должна стать видна раньше,
+    ///    чем мы начнём читать разделяемые указатели.
+    /// 4) Если так настроено (`advance_frequency`), пробуем продвинуть эпоху.
+    fn pin(&self) {
+        let count = self.guard_count.get();
+        self.guard_count.set(count + 1);
+        if count > 0 {
+            return;
+        }
+
+        let global_epoch = self.global().global_epoch.load(Ordering::Relaxed);
+        self.participant()
+            .local_epoch
+            .store(global_epoch.pinned(), Ordering::Relaxed);
+        atomic::fence(Ordering::SeqCst);
+        self.epoch.set(global_epoch);
+
+        let global = self.global();
+        if global.config.detects_stalls() {
+            let now = global.config.stall_timeout.map(|_| global.now_nanos());
+            self.participant().stall.on_pin(now);
+        }
+
+        let pins = self.pin_count.get().wrapping_add(1);
+        self.pin_count.set(pins);
+        if self.global().config.should_advance_on_pin(pins) {
+            self.attempt_advance_epoch();
+        }
+    }
+
+    /// unpin(): уменьшаем счётчик; последний Guard снимает закрепление.
+    /// Если работает фоновый поток, сбрасываем ему локальный мусор.
+    fn unpin(&self) {
+        let count = self.guard_count.get();
+        self.guard_count.set(count - 1);
+        if count == 1 {
+            self.participant()
+                .local_epoch
+                .store(Epoch::starting(), Ordering::Release);
+            self.epoch.set(Epoch::starting());
+
+            if self.global().background.load(Ordering::Relaxed) > 0 {
+                self.flush_to_global();
+            }
+        }
+    }
+
+    /// Размер локальной корзины в байтах.
+    fn pending_bytes(&self) -> usize {
+        self.participant().pending_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Публикует размер локальной корзины для статистики.
+    fn set_pending(&self, len: usize, bytes: usize) {
+        let thr = self.participant();
+        thr.pending.store(len, Ordering::Relaxed);
+        thr.pending_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    /// Переносит весь локальный мусор в глобальный.
+    fn flush_to_global(&self) {
+        let garbage = std::mem::take(unsafe { &mut *self.garbage.get() });
+        self.set_pending(0, 0);
+        if !garbage.is_empty() {
+            self.global().global_garbage.lock().unwrap().extend(garbage);
+        }
+    }
+
+    /// retire():
+    /// Откладываем указатель в локальный "мусор".
+    /// Если там стало много (см. `Config`), пробуем продвинуть эпоху.
+    fn retire(&self, deferred: Deferred, size: usize) {
+        atomic::fence(Ordering::SeqCst);
+        let epoch = self.global().global_epoch.load(Ordering::Relaxed);
+
+        // Безопасно: garbage трогает только владеющий поток (Local !Send),
+        // и ссылка не переживает этот вызов.
+        let len = unsafe {
+            let garbage = &mut *self.garbage.get();
+            garbage.push_back(Retired {
+                deferred,
+                epoch,
+                size,
+            });
+            garbage.len()
+        };
+        let bytes = self.pending_bytes() + size;
+        self.set_pending(len, bytes);
+
+        // Если накопилось много объектов — пробуем продвинуть эпоху
+        if self.global().config.bag_is_full(len, bytes) {
+            self.attempt_advance_epoch();
+        }
+    }
+
+    /// attempt_advance_epoch():
+    /// 1) Пробуем продвинуть глобальную эпоху (см. `Global::try_advance`).
+    /// 2) Освобождаем локальный и глобальный мусор, чья эпоха истекла.
+    ///
+    /// Возвращает false, если эпоху сдвинуть не удалось.
+    fn attempt_advance_epoch(&self) -> bool {
+        let global = self.global();
+        let new_epoch = match global.try_advance(Some(self.index)) {
+            Ok(epoch) => epoch,
+            Err(blocker) => {
+                if let Some(index) = blocker {
+                    global.on_blocked(index);
+                }
+                return false;
+            }
+        };
+
+        // Сначала отбираем готовые объекты, и только потом вызываем функции:
+        // отложенная функция может сама вызвать retire() на этом же участнике.
+        let mut ready = Vec::new();
+        unsafe {
+            let garbage = &mut *self.garbage.get();
+            // Эпохи в локальной очереди не убывают — достаточно смотреть в начало.
+            while garbage.front().is_some_and(|r| r.is_expired(new_epoch)) {
+                let r = garbage.pop_front().unwrap();
+                self.set_pending(garbage.len(), self.pending_bytes() - r.size);
+                ready.push(r);
+            }
+        }
+        global.collect_global(new_epoch, &mut ready);
+
+        global.reclaim(ready);
+        true
+    }
+
+    /// flush(): продвигаем эпоху и собираем мусор, пока есть что собирать.
+    ///
+    /// Объект освобождается через две эпохи после retire, поэтому делаем
+    /// до трёх шагов. Если кто-то (в том числе сам участник) закреплён,
+    /// эпоха упрётся в него и flush остановится раньше.
+    fn flush(&self) {
+        for _ in 0..3 {
+            if !self.attempt_advance_epoch() {
+                break;
+            }
+            let local_empty = unsafe { (*self.garbage.get()).is_empty() };
+            if local_empty && self.global().global_garbage.lock().unwrap().is_empty() {
+                break;
+            }
+        }
+    }
+
+    /// Вызывается, когда не осталось ни LocalHandle, ни Guard:
+    /// освобождаем слот, а недоосвобождённый мусор передаём в глобальный —
+    /// его освободит следующий участник, продвинувший эпоху.
+    fn finalize(ptr: *const Local) {
+        let local = unsafe { Box::from_raw(ptr as *mut Local) };
+        local.flush_to_global();
+
+        let thr = local.participant();
+        thr.local_epoch.store(Epoch::starting(), Ordering::Release);
+        local.global().threads.release(local.index);
+    }
+}
+
+/// Дескриптор участника коллектора. Принадлежит одному потоку (`!Send`).
+/// При дропе слот освобождается (как только уйдут и все его Guard'ы).
+pub struct LocalHandle {
+    local: *const Local,
+}
+
+impl LocalHandle {
+    fn local(&self) -> &Local {
+        unsafe { &*self.local }
+    }
+
+    /// Закрепляет участника на текущей эпохе.
+    /// Пока жив Guard, объекты, прочитанные под ним, не будут освобождены.
+    pub fn pin(&self) -> Guard<'_> {
+        unsafe { Guard::new(self.local) }
+    }
+
+    /// Номер слота участника в реестре коллектора
+    /// (тот же, что в [`Stats`] и [`StallReport`]).
+    pub fn index(&self) -> usize {
+        self.local().index
+    }
+
+    /// Закреплён ли участник (есть ли живой Guard).
+    pub fn is_pinned(&self) -> bool {
+        self.local().guard_count.get() > 0
+    }
+
+    /// Коллектор, в котором зарегистрирован участник.
+    pub fn collector(&self) -> &Collector {
+        &self.local().collector
+    }
+
+    /// Принудительно продвигает эпоху и собирает мусор (см. [`Guard::flush`]).
+    ///
+    /// Если участник не закреплён и никто другой не держит старую эпоху,
+    /// после вызова весь отложенный до этого мусор освобождён.
+    pub fn flush(&self) {
+        self.local().flush();
+    }
+
+    /// Guard, не привязанный к времени жизни дескриптора.
+    ///
+    /// Корректно, так как Guard сам удерживает `Local` через guard_count.
+    pub(crate) fn pin_unbounded(&self) -> Guard<'static> {
+        unsafe { Guard::new(self.local) }
+    }
+}
+
+impl Drop for LocalHandle {
+    fn drop(&mut self) {
+        let local = self.local();
+        let handles = local.handle_count.get() - 1;
+        local.handle_count.set(handles);
+        if handles == 0 && local.guard_count.get() == 0 {
+            Local::finalize(self.local);
+        }
+    }
+}
+
+//  Guard, возвращаемый из `pin()`.
+//  Пока существует Guard, участник считается "pinned":
+//  - local_epoch.is_pinned().
+//  При дропе последнего Guard делаем `unpin()` (флаг снимается).
+//  Guard закрепляет участника *текущего* потока, поэтому он !Send и !Sync:
+//  это обеспечивает сырой указатель `local` (см. tests/ui/ebr_guard_send.rs).
+pub struct Guard<'a> {
+    local: *const Local,
+    _marker: PhantomData<&'a LocalHandle>,
+}
+
+impl Guard<'_> {
+    /// # Safety
+    ///
+    /// `local` должен указывать на живой `Local`.
+    unsafe fn new(local: *const Local) -> Self {
+        (*local).pin();
+        Guard {
+            local,
+            _marker: PhantomData,
+        }
+    }
+
+    fn local(&self) -> &Local {
+        unsafe { &*self.local }
+    }
+
+    /// Возвращает локальную эпоху, на которую "закрепился" участник.
+    pub fn epoch(&self) -> usize {
+        self.local().epoch.get().value()
+    }
+
+    /// Коллектор, к которому относится Guard.
+    pub fn collector(&self) -> &Collector {
+        &self.local().collector
+    }
+
+    /// Откладывает вызов `f` до момента, когда все участники, закреплённые
+    /// сейчас, открепятся. `f` будет вызвана ровно один раз, возможно
+    /// в другом потоке.
+    pub fn defer<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        unsafe { self.defer_unchecked(f) }
+    }
+
+    /// Как [`Guard::defer`], но без требований `Send` и `'static`.
+    ///
+    /// # Safety
+    ///
+    /// `f` может быть вызвана в другом потоке и после того, как истекут
+    /// все заимствования, которые она захватила; вызывающий гарантирует,
+    /// что это безопасно.
+    pub unsafe fn defer_unchecked<F: FnOnce()>(&self, f: F) {
+        self.local().retire(Deferred::new(f), 0);
+    }
+
+    /// Как [`Guard::defer`], но с подсказкой, сколько байт освободит `f`.
+    /// Подсказка учитывается порогом `Config::byte_threshold`.
+    pub fn defer_with_size<F>(&self, f: F, size: usize)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.local().retire(Deferred::new(f), size);
+    }
+
+    /// Принудительная попытка продвинуть эпоху и собрать мусор.
+    ///
+    /// Сам Guard держит текущую эпоху, поэтому под ним эпоха сдвинется
+    /// не больше чем на один шаг; для полной очистки используйте
+    /// [`LocalHandle::flush`] или [`flush`](super::flush) без Guard.
+    pub fn flush(&self) {
+        self.local().flush();
+    }
+
+    /// Откладывает освобождение объекта, на который указывает `shared`.
+    ///
+    /// # Safety
+    ///
+    /// Объект уже отцеплен от структуры данных (новые читатели его не найдут),
+    /// и никто другой не отложит его освобождение повторно.
+    pub unsafe fn defer_destroy<T>(&self, shared: Shared<'_, T>) {
+        self.local().retire(
+            Deferred::new(move || drop(shared.into_owned())),
+            std::mem::size_of::<T>(),
+        );
+    }
+}
+
+impl Drop for Guard<'_> {
+    fn drop(&mut self) {
+        let local = self.local();
+        local.unpin();
+        if local.guard_count.get() == 0 && local.handle_count.get() == 0 {
+            Local::finalize(self.local);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_collectors_are_independent() {
+        let a = Collector::new();
+        let b = Collector::new();
+        assert_ne!(a, b);
+        assert_eq!(a, a.clone());
+
+        let handle = a.register();
+        for _ in 0..200 {
+            handle.pin().defer(|| ());
+        }
+
+        assert!(a.epoch() > 0, "эпоха коллектора `a` должна сдвинуться");
+        assert_eq!(b.epoch(), 0, "коллектор `b` не должен это заметить");
+    }
+
+    #[test]
+    fn test_nested_pin() {
+        let collector = Collector::new();
+        let handle = collector.register();
+        assert!(!handle.is_pinned());
+
+        let outer = handle.pin();
+        let inner = handle.pin();
+        assert_eq!(outer.epoch(), inner.epoch());
+        drop(inner);
+        assert!(handle.is_pinned(), "внешний Guard всё ещё жив");
+        drop(outer);
+        assert!(!handle.is_pinned());
+    }
+
+    #[test]
+    fn test_pinned_participant_blocks_advance() {
+        let collector = Collector::new();
+        let reader = collector.register();
+        let writer = collector.register();
+
+        let _guard = reader.pin();
+        for _ in 0..200 {
+            writer.pin().defer(|| ());
+        }
+
+        // Читатель закреплён на эпохе 0 => эпоха может сдвинуться максимум на 1
+        assert!(collector.epoch() <= 1);
+    }
+
+    #[test]
+    fn test_retired_objects_are_freed() {
+        let freed = Arc::new(AtomicUsize::new(0));
+
+        let collector = Collector::new();
+        let handle = collector.register();
+        for _ in 0..200 {
+            let freed = freed.clone();
+            handle.pin().defer(move || {
+                freed.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        assert!(freed.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn test_orphaned_garbage_is_freed_by_others() {
+        let freed = Arc::new(AtomicUsize::new(0));
+
+        let collector = Collector::new();
+
+        // Поток оставляет немного мусора (меньше порога) и завершается
+        let (c, f) = (collector.clone(), freed.clone());
+        thread::spawn(move || {
+            let freed = f;
+            let handle = c.register();
+            for _ in 0..10 {
+                let freed = freed.clone();
+                handle.pin().defer(move || {
+                    freed.fetch_add(1, Ordering::Relaxed);
+                });
+            }
+        })
+        .join()
+        .unwrap();
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+
+        // Другой участник продвигает эпоху и подбирает мусор сироты
+        let handle = collector.register();
+        for _ in 0..200 {
+            handle.pin().defer(|| ());
+        }
+        assert_eq!(freed.load(Ordering::Relaxed), 10);
+    }
+
+    #[test]
+    fn test_pending_garbage_is_freed_with_collector() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let handle = collector.register();
+        for _ in 0..10 {
+            let freed = freed.clone();
+            handle.pin().defer(move || {
+                freed.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        drop(handle);
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+
+        drop(collector);
+        assert_eq!(freed.load(Ordering::Relaxed), 10);
+    }
+
+    #[test]
+    fn test_flush_frees_deterministically() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let handle = collector.register();
+        {
+            let guard = handle.pin();
+            let f = freed.clone();
+            guard.defer(move || {
+                f.fetch_add(1, Ordering::Relaxed);
+            });
+
+            // Под Guard эпоха сдвигается максимум на шаг — объект ещё жив
+            guard.flush();
+            assert_eq!(freed.load(Ordering::Relaxed), 0);
+        }
+
+        handle.flush();
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_custom_bag_size() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::with_config(Config {
+            bag_size: 2,
+            ..Config::default()
+        });
+        assert_eq!(collector.config().bag_size, 2);
+
+        let handle = collector.register();
+        for _ in 0..10 {
+            let f = freed.clone();
+            handle.pin().defer(move || {
+                f.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        assert!(freed.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn test_byte_threshold() {
+        let collector = Collector::with_config(Config {
+            byte_threshold: Some(1024),
+            ..Config::default()
+        });
+        let handle = collector.register();
+
+        // Мелкие объекты порог не трогают
+        for _ in 0..10 {
+            handle.pin().defer_with_size(|| (), 8);
+        }
+        assert_eq!(collector.epoch(), 0);
+
+        // Один крупный — сразу попытка продвинуть эпоху
+        handle.pin().defer_with_size(|| (), 4096);
+        assert_eq!(collector.epoch(), 1);
+    }
+
+    #[test]
+    fn test_advance_frequency() {
+        let collector = Collector::with_config(Config {
+            advance_frequency: Some(4),
+            ..Config::default()
+        });
+        let handle = collector.register();
+        for _ in 0..3 {
+            drop(handle.pin());
+        }
+        assert_eq!(collector.epoch(), 0);
+        drop(handle.pin());
+        assert_eq!(collector.epoch(), 1);
+    }
+
+    #[test]
+    fn test_stats() {
+        let collector = Collector::new();
+        let reader = collector.register();
+        let writer = collector.register();
+
+        let stats = collector.stats();
+        assert_eq!(stats.registered, 2);
+        assert_eq!(stats.pinned, 0);
+        assert_eq!(stats.oldest_pinned_epoch, None);
+
+        let guard = reader.pin();
+        for _ in 0..3 {
+            writer.pin().defer_with_size(|| (), 100);
+        }
+        let stats = collector.stats();
+        assert_eq!(stats.pinned, 1);
+        assert_eq!(stats.oldest_pinned_epoch, Some(guard.epoch()));
+        assert_eq!(stats.total_pending(), 3);
+        assert_eq!(stats.total_pending_bytes(), 300);
+        assert_eq!(stats.reclaimed, 0);
+
+        // Отключившийся участник отдаёт мусор в глобальный
+        drop(writer);
+        let stats = collector.stats();
+        assert_eq!(stats.registered, 1);
+        assert_eq!(stats.global_pending, 3);
+        assert_eq!(stats.global_pending_bytes, 300);
+
+        drop(guard);
+        reader.flush();
+        let stats = collector.stats();
+        assert_eq!(stats.total_pending(), 0);
+        assert_eq!(stats.reclaimed, 3);
+        assert_eq!(stats.reclaimed_bytes, 300);
+    }
+
+    #[test]
+    fn test_stall_detected_by_epochs() {
+        let collector = Collector::with_config(Config {
+            stall_epochs: Some(3),
+            ..Config::default()
+        });
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let r = reports.clone();
+        collector.on_stall(move |report| r.lock().unwrap().push(report.clone()));
+
+        let reader = collector.register();
+        let writer = collector.register();
+        let guard = reader.pin();
+        for _ in 0..10 {
+            writer.flush();
+        }
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1, "об одном закреплении сообщаем один раз");
+        assert_eq!(reports[0].index, reader.index());
+        assert_eq!(reports[0].pinned_epoch, guard.epoch());
+        assert!(reports[0].blocked_advances >= 3);
+        assert!(reports[0].pinned_for.is_none());
+    }
+
+    #[test]
+    fn test_stall_detected_by_timeout() {
+        let collector = Collector::with_config(Config {
+            stall_timeout: Some(Duration::from_millis(10)),
+            ..Config::default()
+        });
+        let reader = collector.register();
+        let _guard = reader.pin();
+        assert!(collector.detect_stalls().is_empty());
+
+        std::thread::sleep(Duration::from_millis(20));
+        let reports = collector.detect_stalls();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].index, reader.index());
+        assert!(reports[0].pinned_for.unwrap() >= Duration::from_millis(10));
+        #[cfg(feature = "ebr-backtrace")]
+        assert!(reports[0].backtrace.is_some());
+
+        // Повторно не сообщаем
+        assert!(collector.detect_stalls().is_empty());
+    }
+
+    /// Участник, вызвавший flush под собственным Guard, сам себе мешает,
+    /// но зависшим не считается.
+    #[test]
+    fn test_flush_under_own_guard_is_not_a_stall() {
+        let collector = Collector::with_config(Config {
+            stall_epochs: Some(1),
+            ..Config::default()
+        });
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let r = reports.clone();
+        collector.on_stall(move |report| r.lock().unwrap().push(report.clone()));
+
+        let handle = collector.register();
+        let guard = handle.pin();
+        for _ in 0..10 {
+            guard.flush();
+            handle.flush();
+        }
+        assert!(reports.lock().unwrap().is_empty());
+        assert!(collector.detect_stalls().is_empty());
+
+        // Чужое закрепление по-прежнему обнаруживается
+        let other = collector.register();
+        let other_guard = other.pin();
+        drop(guard);
+        for _ in 0..3 {
+            handle.flush();
+        }
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].index, other.index());
+        drop(other_guard);
+    }
+
+    #[test]
+    fn test_repin_resets_stall() {
+        let collector = Collector::with_config(Config {
+            stall_epochs: Some(2),
+            ..Config::default()
+        });
+        let reader = collector.register();
+        let writer = collector.register();
+
+        let guard = reader.pin();
+        writer.flush();
+        writer.flush();
+        drop(guard);
+
+        // Новое закрепление начинает счёт заново
+        let _guard = reader.pin();
+        assert!(collector.detect_stalls().is_empty());
+    }
+
+    /// Последняя эпоха перед переполнением счётчика.
+    const LAST_EPOCH: usize = usize::MAX >> 1;
+
+    fn near_wrap() -> Collector {
+        Collector::with_epoch(Config::default(), Epoch::new(LAST_EPOCH - 1))
+    }
+
+    #[test]
+    fn test_epoch_wraps_around() {
+        let collector = near_wrap();
+        let handle = collector.register();
+        assert_eq!(collector.epoch(), LAST_EPOCH - 1);
+
+        handle.flush();
+        assert_eq!(collector.epoch(), LAST_EPOCH);
+        handle.flush();
+        assert_eq!(collector.epoch(), 0, "счётчик переполнился");
+        handle.flush();
+        assert_eq!(collector.epoch(), 1);
+    }
+
+    #[test]
+    fn test_reclaim_across_wraparound() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = near_wrap();
+        let handle = collector.register();
+
+        // Откладываем на эпохах LAST_EPOCH - 1, LAST_EPOCH и 0
+        for _ in 0..3 {
+            let f = freed.clone();
+            handle.pin().defer(move || {
+                f.fetch_add(1, Ordering::Relaxed);
+            });
+            let guard = handle.pin();
+            guard.flush();
+        }
+        assert!(freed.load(Ordering::Relaxed) < 3);
+
+        handle.flush();
+        assert_eq!(freed.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_pinned_reader_blocks_across_wraparound() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = near_wrap();
+        let reader = collector.register();
+        let writer = collector.register();
+        writer.flush();
+        assert_eq!(collector.epoch(), LAST_EPOCH);
+
+        // Читатель закреплён на последней эпохе перед переполнением
+        let guard = reader.pin();
+        {
+            let f = freed.clone();
+            writer.pin().defer(move || {
+                f.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        for _ in 0..10 {
+            writer.flush();
+        }
+        // Эпоха переполнилась до 0 и дальше не идёт: читатель на LAST_EPOCH
+        // "старше" 0, хотя LAST_EPOCH > 0 как число.
+        assert_eq!(collector.epoch(), 0);
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+
+        let stats = collector.stats();
+        assert_eq!(stats.oldest_pinned_epoch, Some(LAST_EPOCH));
+
+        drop(guard);
+        writer.flush();
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_slot_is_released_on_drop() {
+        let collector = Collector::new();
+        for _ in 0..MAX_THREADS * 2 {
+            let handle = collector.register();
+            drop(handle.pin());
+        }
+    }
+
+    #[test]
+    fn test_guard_outlives_handle() {
+        let collector = Collector::new();
+        let handle = collector.register();
+        let guard = handle.pin_unbounded();
+        drop(handle);
+        assert_eq!(guard.collector(), &collector);
+        drop(guard);
+
+        // Слот освобождён => можно занять все MAX_THREADS
+        let handles: Vec<_> = (0..MAX_THREADS).map(|_| collector.register()).collect();
+        assert_eq!(handles.len(), MAX_THREADS);
+    }
+}
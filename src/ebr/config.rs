@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+/// Настройки коллектора.
+///
+/// Значения по умолчанию повторяют прежнее поведение: попытка продвинуть
+/// эпоху делается, когда в локальной корзине набирается 64 объекта.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// Сколько объектов в локальной корзине запускают попытку
+    /// продвинуть эпоху и собрать мусор.
+    pub bag_size: usize,
+    /// Порог по суммарному размеру локальной корзины в байтах
+    /// (по подсказкам из `defer_with_size` / `defer_destroy`).
+    /// `None` — порог выключен.
+    pub byte_threshold: Option<usize>,
+    /// Пробовать продвинуть эпоху на каждом N-м `pin()`, даже если
+    /// поток ничего не откладывает. `None` — только по порогам корзины.
+    pub advance_frequency: Option<usize>,
+    /// Считать читателя зависшим, если столько эпох подряд (попыток
+    /// продвижения) не удалось сменить из-за него. `None` — не следить.
+    pub stall_epochs: Option<usize>,
+    /// Считать читателя зависшим, если он закреплён дольше этого времени.
+    /// Включает замер времени в каждом pin(). `None` — не следить.
+    pub stall_timeout: Option<Duration>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bag_size: 64,
+            byte_threshold: None,
+            advance_frequency: None,
+            stall_epochs: None,
+            stall_timeout: None,
+        }
+    }
+}
+
+impl Config {
+    /// Пора ли собирать мусор при корзине из `len` объектов на `bytes` байт.
+    pub(crate) fn bag_is_full(&self, len: usize, bytes: usize) -> bool {
+        len >= self.bag_size || self.byte_threshold.is_some_and(|limit| bytes >= limit)
+    }
+
+    /// Включено ли обнаружение зависших читателей.
+    pub(crate) fn detects_stalls(&self) -> bool {
+        self.stall_epochs.is_some() || self.stall_timeout.is_some()
+    }
+
+    /// Пора ли пробовать продвинуть эпоху на `pin_count`-м pin().
+    pub(crate) fn should_advance_on_pin(&self, pin_count: usize) -> bool {
+        self.advance_frequency
+            .is_some_and(|n| pin_count.is_multiple_of(n.max(1)))
+    }
+}
@@ -0,0 +1,143 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr,
+};
+
+/// Сколько машинных слов замыкания храним прямо в `Deferred`, без Box.
+/// Трёх слов хватает на `defer_destroy` (один указатель) и на типичные
+/// замыкания вида "указатель + Arc + число".
+const DATA_WORDS: usize = 3;
+
+type Data = [usize; DATA_WORDS];
+
+/// Отложенная функция (`FnOnce()`), которую EBR вызовет, когда
+/// освобождение станет безопасным.
+///
+/// Небольшие замыкания (до `DATA_WORDS` слов) хранятся inline,
+/// большие — в `Box`, поэтому типичный retire не аллоцирует.
+pub(crate) struct Deferred {
+    call: unsafe fn(*mut u8),
+    data: MaybeUninit<Data>,
+    _marker: PhantomData<*mut ()>, // !Send + !Sync
+}
+
+/// Помещается ли замыкание `F` в inline-хранилище.
+const fn fits_inline<F>() -> bool {
+    mem::size_of::<F>() <= mem::size_of::<Data>() && mem::align_of::<F>() <= mem::align_of::<Data>()
+}
+
+impl Deferred {
+    /// Упаковывает замыкание.
+    pub(crate) fn new<F: FnOnce()>(f: F) -> Self {
+        unsafe {
+            if fits_inline::<F>() {
+                let mut data = MaybeUninit::<Data>::uninit();
+                ptr::write(data.as_mut_ptr() as *mut F, f);
+
+                unsafe fn call<F: FnOnce()>(raw: *mut u8) {
+                    let f: F = ptr::read(raw as *mut F);
+                    f();
+                }
+
+                Deferred {
+                    call: call::<F>,
+                    data,
+                    _marker: PhantomData,
+                }
+            } else {
+                let b: Box<F> = Box::new(f);
+                let mut data = MaybeUninit::<Data>::uninit();
+                ptr::write(data.as_mut_ptr() as *mut Box<F>, b);
+
+                unsafe fn call<F: FnOnce()>(raw: *mut u8) {
+                    let b: Box<F> = ptr::read(raw as *mut Box<F>);
+                    (*b)();
+                }
+
+                Deferred {
+                    call: call::<F>,
+                    data,
+                    _marker: PhantomData,
+                }
+            }
+        }
+    }
+
+    /// Вызывает отложенную функцию (ровно один раз — `self` поглощается).
+    pub(crate) fn call(mut self) {
+        let call = self.call;
+        unsafe { call(self.data.as_mut_ptr() as *mut u8) };
+    }
+}
+
+impl fmt::Debug for Deferred {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Deferred { .. }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn fits<F: FnOnce()>(_: &F) -> bool {
+        fits_inline::<F>()
+    }
+
+    #[test]
+    fn test_small_closure_is_inline() {
+        let fired = Rc::new(Cell::new(0));
+        let f = fired.clone();
+        let ptr = Box::into_raw(Box::new(0u64));
+        let closure = move || {
+            drop(unsafe { Box::from_raw(ptr) });
+            f.set(f.get() + 1)
+        };
+        assert!(fits(&closure));
+
+        let d = Deferred::new(closure);
+        assert_eq!(Rc::strong_count(&fired), 2, "замыкание ещё не вызвано");
+
+        d.call();
+        assert_eq!(fired.get(), 1);
+        assert_eq!(Rc::strong_count(&fired), 1, "захваченный Rc освобождён");
+    }
+
+    #[test]
+    fn test_large_closure_is_boxed() {
+        let fired = Rc::new(Cell::new(0));
+        let f = fired.clone();
+        let payload = [7u64; 16];
+
+        let closure = move || f.set(payload.iter().sum());
+        assert!(!fits(&closure));
+
+        let d = Deferred::new(closure);
+        d.call();
+        assert_eq!(fired.get(), 7 * 16);
+        assert_eq!(Rc::strong_count(&fired), 1);
+    }
+
+    #[test]
+    fn test_over_aligned_closure() {
+        #[repr(align(64))]
+        struct Aligned(u8);
+
+        let fired = Rc::new(Cell::new(0));
+        let f = fired.clone();
+        let a = Aligned(5);
+        let closure = move || {
+            let a = a;
+            f.set(a.0)
+        };
+        assert!(!fits(&closure));
+
+        let d = Deferred::new(closure);
+        d.call();
+        assert_eq!(fired.get(), 5);
+    }
+}
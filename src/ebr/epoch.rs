@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Эпоха с флагом "закреплён" в младшем бите.
+///
+/// Счётчик эпох рано или поздно переполняется, поэтому эпохи сравниваются
+/// только по модулю: через [`Epoch::wrapping_sub`], а не через `<`.
+/// Это корректно, пока разница между сравниваемыми эпохами мала (на практике
+/// не больше 2: закреплённый участник не даёт эпохе уйти дальше).
+///
+/// Флаг в младшем бите заменяет прежнее "особое" значение `usize::MAX` для
+/// незакреплённого участника, которое счётчик мог однажды реально принять.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Epoch {
+    /// Младший бит — флаг "закреплён", остальные биты — сама эпоха.
+    data: usize,
+}
+
+impl Epoch {
+    /// Начальная эпоха (незакреплённая).
+    pub(crate) const fn starting() -> Self {
+        Epoch { data: 0 }
+    }
+
+    /// Эпоха с номером `value` (незакреплённая).
+    #[cfg(test)]
+    pub(crate) const fn new(value: usize) -> Self {
+        Epoch { data: value << 1 }
+    }
+
+    /// Номер эпохи (без флага). Растёт по модулю `usize::MAX >> 1`.
+    pub(crate) const fn value(self) -> usize {
+        self.data >> 1
+    }
+
+    /// Разница `self - rhs` в эпохах с учётом переполнения.
+    /// Флаг "закреплён" не учитывается.
+    pub(crate) fn wrapping_sub(self, rhs: Self) -> isize {
+        (self.data & !1).wrapping_sub(rhs.data & !1) as isize >> 1
+    }
+
+    /// Закреплён ли участник на этой эпохе.
+    pub(crate) fn is_pinned(self) -> bool {
+        self.data & 1 == 1
+    }
+
+    /// Та же эпоха с флагом "закреплён".
+    pub(crate) fn pinned(self) -> Self {
+        Epoch {
+            data: self.data | 1,
+        }
+    }
+
+    /// Та же эпоха без флага.
+    pub(crate) fn unpinned(self) -> Self {
+        Epoch {
+            data: self.data & !1,
+        }
+    }
+
+    /// Следующая эпоха (с переполнением через ноль).
+    pub(crate) fn successor(self) -> Self {
+        Epoch {
+            data: self.data.wrapping_add(2),
+        }
+    }
+}
+
+/// Атомарная [`Epoch`].
+#[derive(Debug, Default)]
+pub(crate) struct AtomicEpoch {
+    data: AtomicUsize,
+}
+
+impl AtomicEpoch {
+    pub(crate) const fn new(epoch: Epoch) -> Self {
+        AtomicEpoch {
+            data: AtomicUsize::new(epoch.data),
+        }
+    }
+
+    pub(crate) fn load(&self, ord: Ordering) -> Epoch {
+        Epoch {
+            data: self.data.load(ord),
+        }
+    }
+
+    pub(crate) fn store(&self, epoch: Epoch, ord: Ordering) {
+        self.data.store(epoch.data, ord);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_flag() {
+        let e = Epoch::new(5);
+        assert!(!e.is_pinned());
+        assert!(e.pinned().is_pinned());
+        assert_eq!(e.pinned().unpinned(), e);
+        assert_eq!(e.pinned().value(), 5);
+        assert_ne!(Epoch::starting().pinned(), Epoch::starting());
+    }
+
+    #[test]
+    fn test_successor_wraps() {
+        let last = Epoch::new(usize::MAX >> 1);
+        let next = last.successor();
+        assert_eq!(next, Epoch::starting());
+        assert_eq!(next.value(), 0);
+        assert_eq!(next.wrapping_sub(last), 1);
+        assert_eq!(last.wrapping_sub(next), -1);
+        assert_eq!(next.successor().wrapping_sub(last.pinned()), 2);
+    }
+}
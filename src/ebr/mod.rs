@@ -0,0 +1,107 @@
+//! Epoch-Based Reclamation (EBR).
+//!
+//! Каждый [`Collector`] владеет своей глобальной эпохой и реестром
+//! участников. Поток регистрируется через [`Collector::register`], получает
+//! [`LocalHandle`] и закрепляется (`pin`) на время работы с разделяемыми
+//! указателями.
+//!
+//! Указатели на разделяемые объекты типизированы: [`Atomic<T>`] загружается
+//! под `&'g Guard` и даёт [`Shared<'g, T>`], который не переживёт Guard;
+//! отцепленный объект откладывается через [`Guard::defer_destroy`].
+//! Произвольную отложенную работу можно передать в [`Guard::defer`].
+//!
+//! Очистка по умолчанию происходит только внутри retire; для потоков, которые
+//! надолго замолкают, есть фоновый поток [`BackgroundReclaimer`].
+//!
+//! Накопление мусора видно через [`Collector::stats`], а читателей, которые
+//! слишком долго держат Guard, ловит [`Collector::on_stall`].
+//!
+//! Свободные функции [`pin`], [`retire`] и [`unregister_thread`] — тонкая
+//! обёртка над коллектором по умолчанию с дескриптором в thread_local.
+
+use std::{cell::RefCell, time::Duration};
+
+use crate::registry::{self, ThreadHandle};
+
+mod atomic;
+mod background;
+mod collector;
+mod config;
+pub(crate) mod deferred;
+mod epoch;
+mod stall;
+mod stats;
+
+pub use atomic::{Atomic, CompareExchangeError, Owned, Pointer, Shared};
+pub use background::BackgroundReclaimer;
+pub use collector::{Collector, Guard, LocalHandle};
+pub use config::Config;
+pub use stall::StallReport;
+pub use stats::{ParticipantStats, Stats};
+
+/// Коллектор по умолчанию с ленивой (Lazy) инициализацией.
+static DEFAULT_COLLECTOR: once_cell::sync::Lazy<Collector> =
+    once_cell::sync::Lazy::new(Collector::new);
+
+// thread_local! хранит Option<LocalHandle> для каждого потока.
+// Если None, значит поток ещё не зарегистрирован в коллекторе по умолчанию.
+thread_local! {
+    static HANDLE: ThreadHandle<LocalHandle> = const { RefCell::new(None) };
+}
+
+/// Коллектор, которым пользуются свободные функции модуля.
+pub fn default_collector() -> &'static Collector {
+    &DEFAULT_COLLECTOR
+}
+
+/// Дескриптор текущего потока в коллекторе по умолчанию.
+fn with_handle<R>(f: impl Fn(&LocalHandle) -> R) -> R {
+    registry::with_thread_handle(&HANDLE, || DEFAULT_COLLECTOR.register(), f)
+}
+
+// pin():
+// 1) Если поток ещё не зарегистрирован, регистрируем его в коллекторе по умолчанию.
+// 2) Закрепляемся на текущей глобальной эпохе.
+// Возвращаем Guard, который при дропе unpin'ит поток.
+pub fn pin() -> Guard<'static> {
+    // Guard сам удерживает участника, даже если дескриптор временный.
+    with_handle(LocalHandle::pin_unbounded)
+}
+
+/// retire():
+/// Откладываем вызов `deleter(ptr)` в локальный "мусор" участника, к которому
+/// относится `guard` (через [`Guard::defer_unchecked`]).
+/// Если там стало много (см. [`Config`]), пробуем продвинуть эпоху.
+pub fn retire<T>(ptr: *mut T, deleter: fn(*mut T), guard: &Guard) {
+    unsafe { guard.defer_unchecked(move || deleter(ptr)) };
+}
+
+/// flush():
+/// Принудительно продвигает эпоху коллектора по умолчанию и собирает мусор.
+/// Если поток не держит Guard и никто другой не закреплён на старой эпохе,
+/// после вызова весь ранее отложенный мусор освобождён — удобно в тестах
+/// и при завершении работы.
+pub fn flush() {
+    with_handle(LocalHandle::flush);
+}
+
+/// Снимок статистики коллектора по умолчанию (см. [`Collector::stats`]).
+pub fn stats() -> Stats {
+    DEFAULT_COLLECTOR.stats()
+}
+
+/// Опционально — unregister_thread():
+/// Отключает поток от коллектора по умолчанию и освобождает его слот.
+///
+/// Недоосвобождённый мусор потока не удаляется сразу (его ещё могут
+/// читать другие потоки), а передаётся коллектору и будет освобождён
+/// при следующем продвижении эпохи.
+pub fn unregister_thread() {
+    registry::unregister_thread_handle(&HANDLE);
+}
+
+/// Запускает фоновый поток очистки для коллектора по умолчанию.
+/// Поток работает, пока жив возвращённый [`BackgroundReclaimer`].
+pub fn start_background(interval: Duration) -> BackgroundReclaimer {
+    BackgroundReclaimer::start(&DEFAULT_COLLECTOR, interval)
+}
@@ -0,0 +1,92 @@
+#[cfg(feature = "ebr-backtrace")]
+use std::sync::Mutex;
+use std::{
+    backtrace::Backtrace,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Обработчик зависших читателей (см. [`Collector::on_stall`](super::Collector::on_stall)).
+pub(crate) type StallHandler = Arc<dyn Fn(&StallReport) + Send + Sync>;
+
+/// Отчёт о зависшем читателе: участник слишком долго держит Guard
+/// и не даёт продвинуть эпоху, из-за чего мусор копится без ограничений.
+#[derive(Debug, Clone)]
+pub struct StallReport {
+    /// Номер слота участника (см. [`LocalHandle::index`](super::LocalHandle::index)).
+    pub index: usize,
+    /// Эпоха, на которой участник закреплён.
+    pub pinned_epoch: usize,
+    /// Глобальная эпоха в момент обнаружения.
+    pub global_epoch: usize,
+    /// Сколько попыток продвинуть эпоху упёрлись в этого участника.
+    pub blocked_advances: usize,
+    /// Сколько времени участник закреплён (если включён `stall_timeout`).
+    pub pinned_for: Option<Duration>,
+    /// Где участник закрепился (только с feature `ebr-backtrace`).
+    pub backtrace: Option<Arc<Backtrace>>,
+}
+
+/// Состояние слота для обнаружения зависаний. Пишет владелец при pin(),
+/// читают те, кто пытается продвинуть эпоху, и фоновый поток.
+#[derive(Default)]
+pub(crate) struct StallState {
+    /// Момент pin() в наносекундах от создания коллектора (+1; 0 — неизвестно).
+    pinned_at: AtomicU64,
+    /// Сколько попыток продвинуть эпоху упёрлись в участника с момента pin().
+    blocked: AtomicUsize,
+    /// Об этом закреплении уже сообщили.
+    reported: AtomicBool,
+    #[cfg(feature = "ebr-backtrace")]
+    backtrace: Mutex<Option<Arc<Backtrace>>>,
+}
+
+impl StallState {
+    /// Сбрасывает состояние при новом (внешнем) pin().
+    pub(crate) fn on_pin(&self, now_nanos: Option<u64>) {
+        self.blocked.store(0, Ordering::Relaxed);
+        self.reported.store(false, Ordering::Relaxed);
+        self.pinned_at
+            .store(now_nanos.map_or(0, |n| n + 1), Ordering::Relaxed);
+
+        #[cfg(feature = "ebr-backtrace")]
+        {
+            *self.backtrace.lock().unwrap() = Some(Arc::new(Backtrace::force_capture()));
+        }
+    }
+
+    /// Участник помешал продвинуть эпоху.
+    pub(crate) fn note_blocked(&self) -> usize {
+        self.blocked.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub(crate) fn blocked(&self) -> usize {
+        self.blocked.load(Ordering::Relaxed)
+    }
+
+    /// Сколько участник закреплён, если момент pin() известен.
+    pub(crate) fn pinned_for(&self, now_nanos: u64) -> Option<Duration> {
+        match self.pinned_at.load(Ordering::Relaxed) {
+            0 => None,
+            at => Some(Duration::from_nanos(now_nanos.saturating_sub(at - 1))),
+        }
+    }
+
+    /// Отмечает, что о закреплении сообщили. Возвращает false, если уже сообщали.
+    pub(crate) fn mark_reported(&self) -> bool {
+        !self.reported.swap(true, Ordering::Relaxed)
+    }
+
+    #[cfg(feature = "ebr-backtrace")]
+    pub(crate) fn backtrace(&self) -> Option<Arc<Backtrace>> {
+        self.backtrace.lock().unwrap().clone()
+    }
+
+    #[cfg(not(feature = "ebr-backtrace"))]
+    pub(crate) fn backtrace(&self) -> Option<Arc<Backtrace>> {
+        None
+    }
+}
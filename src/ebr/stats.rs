@@ -0,0 +1,56 @@
+/// Снимок состояния коллектора (см. [`Collector::stats`](super::Collector::stats)).
+///
+/// Значения собираются без остановки участников, поэтому это
+/// приблизительная картина: счётчики разных потоков читаются не одновременно.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Текущая глобальная эпоха.
+    pub epoch: usize,
+    /// Сколько участников зарегистрировано.
+    pub registered: usize,
+    /// Сколько из них сейчас закреплено (держат Guard).
+    pub pinned: usize,
+    /// Самая старая эпоха среди закреплённых участников.
+    /// Если она сильно отстаёт от `epoch`, какой-то читатель застрял.
+    pub oldest_pinned_epoch: Option<usize>,
+    /// Состояние каждого зарегистрированного участника.
+    pub participants: Vec<ParticipantStats>,
+    /// Объектов в глобальном мусоре (сироты и сброшенные корзины).
+    pub global_pending: usize,
+    /// Байт в глобальном мусоре (по подсказкам размера).
+    pub global_pending_bytes: usize,
+    /// Сколько отложенных функций выполнено за всё время.
+    pub reclaimed: usize,
+    /// Сколько байт освобождено за всё время (по подсказкам размера).
+    pub reclaimed_bytes: usize,
+}
+
+impl Stats {
+    /// Всего объектов, ожидающих освобождения (локально и глобально).
+    pub fn total_pending(&self) -> usize {
+        self.global_pending + self.participants.iter().map(|p| p.pending).sum::<usize>()
+    }
+
+    /// Всего байт, ожидающих освобождения.
+    pub fn total_pending_bytes(&self) -> usize {
+        self.global_pending_bytes
+            + self
+                .participants
+                .iter()
+                .map(|p| p.pending_bytes)
+                .sum::<usize>()
+    }
+}
+
+/// Состояние одного участника.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParticipantStats {
+    /// Номер слота в реестре коллектора.
+    pub index: usize,
+    /// Эпоха, на которой участник закреплён (`None`, если не закреплён).
+    pub pinned_epoch: Option<usize>,
+    /// Объектов в локальной корзине.
+    pub pending: usize,
+    /// Байт в локальной корзине.
+    pub pending_bytes: usize,
+}
@@ -0,0 +1,448 @@
+//! Неограниченная MPMC-очередь на fetch-and-add (в духе LCRQ/LPRQ).
+//!
+//! В [`MSQueue`](crate::ms_queue::MSQueue) все производители соревнуются
+//! CAS'ом за один `tail.next`, а потребители — за `head`: при росте числа
+//! ядер большинство CAS проигрывает и повторяется. Здесь очередь — список
+//! сегментов-колец фиксированного размера, связанных как в MS-Queue, а место
+//! внутри сегмента раздаёт `fetch_add` по индексам (как `write_index`/
+//! `read_index` у [`RingBuffer`](crate::ring_buffer::RingBuffer)): FAA
+//! не проигрывает, и каждый поток сразу получает свою ячейку.
+//!
+//! Ячейка проходит состояния `EMPTY → FULL` (производитель записал значение)
+//! или `EMPTY → TAKEN` (потребитель пришёл раньше и «отравил» её).
+//! Потребитель забирает значение только из `FULL`; производитель,
+//! чью ячейку отравили, берёт значение обратно и пробует следующий индекс.
+//! Сегмент, индексы которого закончились, замыкается: производители
+//! добавляют новый сегмент (CAS на `next`, как в MS-Queue), потребители
+//! сдвигают `head` и откладывают старый сегмент в [`crate::ebr`].
+//!
+//! Отравление при гонке потребителя с производителем делает очередь
+//! lock-free только «в среднем» (как и FAAArrayQueue): при постоянной
+//! гонке производитель может повторять попытки, поэтому перед отравлением
+//! потребитель немного ждёт запаздывающую запись.
+
+use std::cell::UnsafeCell;
+use std::hint;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+use crate::cache_padded::CachePadded;
+use crate::ebr::{self, Atomic, Owned, Shared};
+
+/// Число ячеек в сегменте по умолчанию.
+pub const SEGMENT_SIZE: usize = 1024;
+
+/// Сколько раз потребитель проверяет ячейку, прежде чем отравить её.
+const SPIN: usize = 64;
+
+/// Ячейка ещё не заполнена.
+const EMPTY: u8 = 0;
+/// В ячейке лежит значение.
+const FULL: u8 = 1;
+/// Значение забрано, либо ячейка отравлена потребителем.
+const TAKEN: u8 = 2;
+
+/// Ячейка сегмента. Значение инициализировано ровно в состоянии `FULL`.
+struct Slot<T> {
+    state: AtomicU8,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// Сегмент: кольцо ячеек с собственными индексами и ссылка на следующий.
+struct Segment<T> {
+    /// Следующий индекс для потребителя (может уйти за размер сегмента).
+    deq_idx: CachePadded<AtomicUsize>,
+    /// Следующий индекс для производителя (может уйти за размер сегмента).
+    enq_idx: CachePadded<AtomicUsize>,
+    next: Atomic<Segment<T>>,
+    slots: Box<[Slot<T>]>,
+}
+
+impl<T> Segment<T> {
+    fn new(size: usize) -> Self {
+        Segment {
+            deq_idx: CachePadded::new(AtomicUsize::new(0)),
+            enq_idx: CachePadded::new(AtomicUsize::new(0)),
+            next: Atomic::null(),
+            slots: (0..size)
+                .map(|_| Slot {
+                    state: AtomicU8::new(EMPTY),
+                    value: UnsafeCell::new(MaybeUninit::uninit()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Сегмент, в первой ячейке которого уже лежит `value`.
+    fn with_first(size: usize, value: T) -> Self {
+        let segment = Self::new(size);
+        segment.enq_idx.store(1, Ordering::Relaxed);
+        let slot = &segment.slots[0];
+        unsafe { (*slot.value.get()).write(value) };
+        slot.state.store(FULL, Ordering::Relaxed);
+        segment
+    }
+
+    /// Забирает значение из первой ячейки неопубликованного сегмента
+    /// (обратная операция к [`with_first`](Self::with_first)).
+    fn take_first(&mut self) -> T {
+        let slot = &mut self.slots[0];
+        *slot.state.get_mut() = TAKEN;
+        unsafe { slot.value.get_mut().assume_init_read() }
+    }
+}
+
+/// Значения, оставшиеся в `FULL`, принадлежат сегменту.
+/// Отложенные через EBR сегменты таких ячеек не содержат: каждый их индекс
+/// выдан потребителю, который перевёл ячейку в `TAKEN` до открепления.
+impl<T> Drop for Segment<T> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            if *slot.state.get_mut() == FULL {
+                unsafe { slot.value.get_mut().assume_init_drop() };
+            }
+        }
+    }
+}
+
+/// Неограниченная MPMC-очередь на fetch-and-add, см. модуль.
+pub struct FaaQueue<T> {
+    head: CachePadded<Atomic<Segment<T>>>,
+    tail: CachePadded<Atomic<Segment<T>>>,
+    segment_size: usize,
+}
+
+// Значения передаются между потоками по значению — достаточно T: Send.
+unsafe impl<T: Send> Send for FaaQueue<T> {}
+unsafe impl<T: Send> Sync for FaaQueue<T> {}
+
+impl<T> FaaQueue<T> {
+    /// Создаёт пустую очередь с сегментами по [`SEGMENT_SIZE`] ячеек.
+    pub fn new() -> Self {
+        Self::with_segment_size(SEGMENT_SIZE)
+    }
+
+    /// Создаёт пустую очередь с сегментами по `segment_size` ячеек.
+    ///
+    /// # Panics
+    ///
+    /// Если `segment_size == 0`.
+    pub fn with_segment_size(segment_size: usize) -> Self {
+        assert!(segment_size > 0, "segment size must be positive");
+        let first = Atomic::new(Segment::new(segment_size));
+        // Пока очередь никому не видна, сегмент можно разделить между head и tail
+        let tail = unsafe { first.load_unprotected(Ordering::Relaxed) };
+        FaaQueue {
+            head: CachePadded::new(first),
+            tail: CachePadded::new(Atomic::from(tail)),
+            segment_size,
+        }
+    }
+
+    /// Добавляет элемент в конец очереди.
+    pub fn push(&self, value: T) {
+        let guard = &ebr::pin();
+        let mut value = value;
+
+        loop {
+            let tail = self.tail.load(Ordering::Acquire, guard);
+            // tail достижим после pin — сегмент не освобождён
+            let segment = unsafe { tail.deref() };
+            let idx = segment.enq_idx.fetch_add(1, Ordering::Relaxed);
+
+            if idx < self.segment_size {
+                let slot = &segment.slots[idx];
+                // Индекс наш: пишем, пока ячейка EMPTY (потребитель её значение не читает)
+                unsafe { (*slot.value.get()).write(value) };
+                match slot
+                    .state
+                    .compare_exchange(EMPTY, FULL, Ordering::Release, Ordering::Relaxed)
+                {
+                    Ok(_) => return,
+                    // Ячейку отравили — забираем значение обратно
+                    Err(_) => value = unsafe { (*slot.value.get()).assume_init_read() },
+                }
+                continue;
+            }
+
+            // Сегмент исчерпан: прицепляем новый или помогаем сдвинуть tail
+            if self.tail.load(Ordering::Acquire, guard) != tail {
+                continue;
+            }
+            let next = segment.next.load(Ordering::Acquire, guard);
+            if !next.is_null() {
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    guard,
+                );
+                continue;
+            }
+
+            let new = Owned::new(Segment::with_first(self.segment_size, value));
+            match segment.next.compare_exchange(
+                Shared::null(),
+                new,
+                Ordering::Release,
+                Ordering::Relaxed,
+                guard,
+            ) {
+                Ok(new) => {
+                    let _ = self.tail.compare_exchange(
+                        tail,
+                        new,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                        guard,
+                    );
+                    return;
+                }
+                // Кто-то прицепил сегмент раньше — значение возвращаем себе
+                Err(err) => value = err.new.into_box().take_first(),
+            }
+        }
+    }
+
+    /// Извлекает элемент из головы; `None`, если очередь пуста.
+    pub fn pop(&self) -> Option<T> {
+        let guard = &ebr::pin();
+
+        loop {
+            let head = self.head.load(Ordering::Acquire, guard);
+            let segment = unsafe { head.deref() };
+
+            // Пусто: все выданные индексы разобраны и следующего сегмента нет.
+            // Проверяем до FAA, чтобы pop на пустой очереди не жёг индексы.
+            let deq = segment.deq_idx.load(Ordering::Acquire);
+            let enq = segment.enq_idx.load(Ordering::Acquire);
+            if deq >= enq && segment.next.load(Ordering::Acquire, guard).is_null() {
+                return None;
+            }
+
+            let idx = segment.deq_idx.fetch_add(1, Ordering::Relaxed);
+            if idx < self.segment_size {
+                let slot = &segment.slots[idx];
+                // Даём запаздывающему производителю дописать, потом отравляем
+                for _ in 0..SPIN {
+                    if slot.state.load(Ordering::Acquire) != EMPTY {
+                        break;
+                    }
+                    hint::spin_loop();
+                }
+                if slot.state.swap(TAKEN, Ordering::Acquire) == FULL {
+                    return Some(unsafe { (*slot.value.get()).assume_init_read() });
+                }
+                continue;
+            }
+
+            // Индексы сегмента кончились: переходим к следующему
+            let next = segment.next.load(Ordering::Acquire, guard);
+            if next.is_null() {
+                return None;
+            }
+            // head не должен обгонять tail: иначе новый производитель найдёт
+            // в tail уже отложенный сегмент. tail назад не ходит, так что
+            // после этой проверки он указывает дальше head.
+            let tail = self.tail.load(Ordering::Acquire, guard);
+            if tail == head {
+                let _ = self.tail.compare_exchange(
+                    tail,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    guard,
+                );
+            }
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Release, Ordering::Relaxed, guard)
+                .is_ok()
+            {
+                // Новые потребители сюда не попадут, а начавшие держат guard
+                unsafe { guard.defer_destroy(head) };
+            }
+        }
+    }
+
+    /// Пуста ли очередь в момент вызова (приблизительно при гонках).
+    pub fn is_empty(&self) -> bool {
+        let guard = &ebr::pin();
+        let segment = unsafe { self.head.load(Ordering::Acquire, guard).deref() };
+        let deq = segment.deq_idx.load(Ordering::Acquire);
+        let enq = segment.enq_idx.load(Ordering::Acquire);
+        deq >= enq && segment.next.load(Ordering::Acquire, guard).is_null()
+    }
+}
+
+impl<T> Default for FaaQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `&mut self`: других потоков нет — освобождаем сегменты от head сразу,
+/// оставшиеся значения дропает `Drop` сегмента.
+impl<T> Drop for FaaQueue<T> {
+    fn drop(&mut self) {
+        let mut cur = unsafe { self.head.load_unprotected(Ordering::Relaxed) };
+        while !cur.is_null() {
+            let segment = unsafe { cur.into_owned() }.into_box();
+            cur = unsafe { segment.next.load_unprotected(Ordering::Relaxed) };
+            drop(segment);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    /// Значение, считающее свои дропы.
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_fifo_single_thread() {
+        let q = FaaQueue::new();
+        assert!(q.is_empty());
+        assert_eq!(q.pop(), None);
+
+        for i in 0..10 {
+            q.push(i);
+        }
+        assert!(!q.is_empty());
+        for i in 0..10 {
+            assert_eq!(q.pop(), Some(i));
+        }
+        assert_eq!(q.pop(), None);
+        assert!(q.is_empty());
+    }
+
+    /// Маленькие сегменты: порядок сохраняется при переходах между ними
+    #[test]
+    fn test_fifo_across_segments() {
+        let q = FaaQueue::with_segment_size(3);
+        for round in 0..5 {
+            for i in 0..7 {
+                q.push(round * 10 + i);
+            }
+            for i in 0..7 {
+                assert_eq!(q.pop(), Some(round * 10 + i));
+            }
+            assert!(q.is_empty());
+            assert_eq!(q.pop(), None);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "segment size must be positive")]
+    fn test_zero_segment_size() {
+        let _ = FaaQueue::<u8>::with_segment_size(0);
+    }
+
+    /// Значения дропаются ровно один раз: снятые — у вызывающего,
+    /// оставшиеся в нескольких сегментах — в Drop очереди
+    #[test]
+    fn test_drop_each_value_once() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let q = FaaQueue::with_segment_size(4);
+        for _ in 0..10 {
+            q.push(DropCounter(Arc::clone(&drops)));
+        }
+        for _ in 0..5 {
+            drop(q.pop());
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), 5);
+
+        drop(q);
+        assert_eq!(drops.load(Ordering::Relaxed), 10);
+        for _ in 0..10 {
+            ebr::flush();
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), 10);
+    }
+
+    /// Производители и потребители одновременно: значения доставлены
+    /// ровно один раз, в том числе когда ячейки отравляются
+    #[test]
+    fn test_mpmc_all_values_delivered() {
+        for segment_size in [2, 64] {
+            let q = Arc::new(FaaQueue::with_segment_size(segment_size));
+            let threads = 4;
+            let per_thread = 1000;
+
+            let handles: Vec<_> = (0..threads)
+                .map(|t| {
+                    let qc = Arc::clone(&q);
+                    thread::spawn(move || {
+                        let mut popped = Vec::new();
+                        for i in 0..per_thread {
+                            qc.push(t * per_thread + i);
+                            popped.extend(qc.pop());
+                        }
+                        popped
+                    })
+                })
+                .collect();
+
+            let mut values: Vec<_> = handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap())
+                .collect();
+            while let Some(x) = q.pop() {
+                values.push(x);
+            }
+            values.sort();
+            assert_eq!(values, (0..threads * per_thread).collect::<Vec<_>>());
+        }
+    }
+
+    /// Значения одного производителя потребители видят в порядке вставки
+    #[test]
+    fn test_per_producer_order() {
+        let q = Arc::new(FaaQueue::with_segment_size(8));
+        let per_thread = 2000;
+
+        let producers: Vec<_> = (0..2)
+            .map(|t| {
+                let qc = Arc::clone(&q);
+                thread::spawn(move || {
+                    for i in 0..per_thread {
+                        qc.push((t, i));
+                    }
+                })
+            })
+            .collect();
+        let consumer = {
+            let qc = Arc::clone(&q);
+            thread::spawn(move || {
+                let mut last = [None; 2];
+                let mut taken = 0;
+                while taken < 2 * per_thread {
+                    match qc.pop() {
+                        Some((t, i)) => {
+                            assert!(last[t] < Some(i));
+                            last[t] = Some(i);
+                            taken += 1;
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+            })
+        };
+        for p in producers {
+            p.join().unwrap();
+        }
+        consumer.join().unwrap();
+        assert!(q.is_empty());
+    }
+}
@@ -0,0 +1,657 @@
+//! Hazard pointers — альтернатива EBR с ограниченным объёмом мусора.
+//!
+//! Поток, который собирается разыменовать разделяемый указатель, публикует
+//! его в своём hazard-слоте ([`HazardPointer::protect`]). Отцепленный объект
+//! откладывается через [`retire`]; когда у потока набирается `threshold`
+//! таких объектов, он сканирует hazard-слоты всех участников и освобождает
+//! всё, что никто не защищает.
+//!
+//! В отличие от EBR зависший читатель удерживает только те объекты, которые
+//! он защищает (не больше `HAZARDS_PER_THREAD`), а не весь мусор.
+//!
+//! Реестр участников общий с [`crate::ebr`]: [`Domain`] (аналог
+//! `Collector`) с фиксированным числом слотов, [`LocalHandle`] на поток,
+//! при отключении потока его мусор переходит к домену и освобождается
+//! другими участниками. Свободные функции работают с доменом по умолчанию.
+
+use std::{
+    cell::{Cell, RefCell, UnsafeCell},
+    fmt,
+    marker::PhantomData,
+    ptr,
+    sync::{
+        atomic::{self, AtomicPtr, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::ebr::deferred::Deferred;
+use crate::registry::{self, Registry, ThreadHandle};
+
+pub use crate::registry::MAX_THREADS;
+
+/// Сколько hazard-слотов у одного участника (сколько указателей поток
+/// может защищать одновременно).
+pub const HAZARDS_PER_THREAD: usize = 4;
+
+/// Порог сканирования по умолчанию: классическое `R = 2 * H`, где
+/// H — общее число hazard-слотов. Так каждый скан освобождает
+/// не меньше половины локального мусора.
+pub const DEFAULT_SCAN_THRESHOLD: usize = 2 * MAX_THREADS * HAZARDS_PER_THREAD;
+
+/// Разделяемое состояние домена:
+/// 1) records — реестр участников с их hazard-указателями.
+/// 2) orphans — мусор, оставшийся от отключившихся участников.
+/// 3) threshold — сколько объектов в локальном списке запускают скан.
+struct DomainInner {
+    records: Registry<Record>,
+    orphans: Mutex<Vec<Retired>>,
+    threshold: usize,
+}
+
+impl Drop for DomainInner {
+    fn drop(&mut self) {
+        // Последний Domain ушёл => участников не осталось (каждый Local
+        // держит клон Domain), значит защищённых указателей тоже нет.
+        for r in self.orphans.get_mut().unwrap().drain(..) {
+            r.free();
+        }
+    }
+}
+
+/// Состояние участника в слоте реестра: указатели, которые он сейчас
+/// защищает (null — свободно).
+struct Record {
+    hazards: [AtomicPtr<()>; HAZARDS_PER_THREAD],
+}
+
+/// Отложенный на освобождение объект: адрес (для сверки с hazard-слотами)
+/// и функция, которая его освободит.
+struct Retired {
+    ptr: *mut (),
+    free: Deferred,
+}
+
+// Retired переезжает в `orphans` и освобождается другим потоком.
+// Сам объект к этому моменту уже недостижим из структуры данных.
+unsafe impl Send for Retired {}
+
+impl Retired {
+    fn free(self) {
+        self.free.call();
+    }
+}
+
+/// Домен hazard pointers: реестр участников и общий мусор.
+/// Клонирование дешёвое: клоны ссылаются на один и тот же домен.
+#[derive(Clone)]
+pub struct Domain {
+    inner: Arc<DomainInner>,
+}
+
+impl Default for Domain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Domain {
+    /// Создаёт домен с порогом сканирования по умолчанию.
+    pub fn new() -> Self {
+        Self::with_threshold(DEFAULT_SCAN_THRESHOLD)
+    }
+
+    /// Создаёт домен, в котором скан запускается, когда в локальном
+    /// списке участника набирается `threshold` объектов.
+    pub fn with_threshold(threshold: usize) -> Self {
+        Domain {
+            inner: Arc::new(DomainInner {
+                records: Registry::new(|| Record {
+                    hazards: array_init::array_init(|_| AtomicPtr::new(ptr::null_mut())),
+                }),
+                orphans: Mutex::new(Vec::new()),
+                threshold: threshold.max(1),
+            }),
+        }
+    }
+
+    /// Регистрирует нового участника.
+    ///
+    /// # Panics
+    ///
+    /// Если все `MAX_THREADS` слотов заняты.
+    pub fn register(&self) -> LocalHandle {
+        let local = Box::new(Local {
+            index: self.inner.records.acquire("hazard domain"),
+            domain: self.clone(),
+            retired: UnsafeCell::new(Vec::new()),
+            used: Cell::new(0),
+            refs: Cell::new(1),
+        });
+        LocalHandle {
+            local: Box::into_raw(local),
+        }
+    }
+
+    /// Порог сканирования.
+    pub fn threshold(&self) -> usize {
+        self.inner.threshold
+    }
+}
+
+impl PartialEq for Domain {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for Domain {}
+
+impl fmt::Debug for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Domain")
+            .field("threshold", &self.inner.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Локальное состояние участника. Живёт, пока на него ссылается
+/// `LocalHandle` или хотя бы один `HazardPointer` (счётчик refs).
+struct Local {
+    index: usize,
+    domain: Domain,
+    retired: UnsafeCell<Vec<Retired>>,
+    /// Битовая маска занятых hazard-слотов.
+    used: Cell<u32>,
+    refs: Cell<usize>,
+}
+
+impl Local {
+    fn record(&self) -> &Record {
+        self.domain.inner.records.get(self.index)
+    }
+
+    /// Занимает свободный hazard-слот, если он есть.
+    fn try_acquire_slot(&self) -> Option<usize> {
+        let used = self.used.get();
+        let slot = (!used).trailing_zeros() as usize;
+        if slot >= HAZARDS_PER_THREAD {
+            return None;
+        }
+        self.used.set(used | (1 << slot));
+        self.refs.set(self.refs.get() + 1);
+        Some(slot)
+    }
+
+    /// Занимает свободный hazard-слот.
+    fn acquire_slot(&self) -> usize {
+        self.try_acquire_slot()
+            .expect("No free hazard slot (increase HAZARDS_PER_THREAD)")
+    }
+
+    fn release_slot(&self, slot: usize) {
+        self.record().hazards[slot].store(ptr::null_mut(), Ordering::Release);
+        self.used.set(self.used.get() & !(1 << slot));
+    }
+
+    /// retire(): кладём объект в локальный список; на пороге — скан.
+    fn retire(&self, r: Retired) {
+        let len = unsafe {
+            let retired = &mut *self.retired.get();
+            retired.push(r);
+            retired.len()
+        };
+        if len >= self.domain.inner.threshold {
+            self.scan();
+        }
+    }
+
+    /// scan():
+    /// 1) Собираем все опубликованные hazard-указатели.
+    /// 2) Освобождаем локальные и осиротевшие объекты, которых среди них нет.
+    /// 3) Остальное оставляем до следующего скана.
+    fn scan(&self) {
+        let inner = &self.domain.inner;
+
+        // Забираем мусор сирот — дальше он наш.
+        let orphans = std::mem::take(&mut *inner.orphans.lock().unwrap());
+        let mut candidates = std::mem::take(unsafe { &mut *self.retired.get() });
+        candidates.extend(orphans);
+        if candidates.is_empty() {
+            return;
+        }
+
+        // Барьер в паре с барьером в protect(): либо мы увидим hazard,
+        // либо читатель увидит, что указатель уже отцеплен.
+        atomic::fence(Ordering::SeqCst);
+        let mut protected: Vec<*mut ()> = inner
+            .records
+            .active()
+            .flat_map(|(_, rec)| rec.hazards.iter())
+            .map(|h| h.load(Ordering::Acquire))
+            .filter(|p| !p.is_null())
+            .collect();
+        protected.sort_unstable();
+
+        let (keep, free): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .partition(|r| protected.binary_search(&r.ptr).is_ok());
+
+        // Возвращаем защищённое до освобождения: deleter может снова вызвать retire().
+        unsafe { (*self.retired.get()).extend(keep) };
+        for r in free {
+            r.free();
+        }
+    }
+
+    fn release(ptr: *const Local) {
+        let local = unsafe { &*ptr };
+        let refs = local.refs.get() - 1;
+        local.refs.set(refs);
+        if refs == 0 {
+            Local::finalize(ptr);
+        }
+    }
+
+    /// Вызывается, когда не осталось ни LocalHandle, ни HazardPointer:
+    /// пытаемся освободить свой мусор, остаток передаём домену.
+    fn finalize(ptr: *const Local) {
+        let local = unsafe { Box::from_raw(ptr as *mut Local) };
+        local.scan();
+
+        let retired = std::mem::take(unsafe { &mut *local.retired.get() });
+        if !retired.is_empty() {
+            local.domain.inner.orphans.lock().unwrap().extend(retired);
+        }
+
+        local.domain.inner.records.release(local.index);
+    }
+}
+
+/// Дескриптор участника домена. Принадлежит одному потоку (`!Send`).
+pub struct LocalHandle {
+    local: *const Local,
+}
+
+impl LocalHandle {
+    fn local(&self) -> &Local {
+        unsafe { &*self.local }
+    }
+
+    /// Занимает hazard-слот участника.
+    ///
+    /// # Panics
+    ///
+    /// Если у участника уже заняты все `HAZARDS_PER_THREAD` слотов.
+    pub fn hazard_pointer(&self) -> HazardPointer<'_> {
+        unsafe { HazardPointer::new(self.local) }
+    }
+
+    /// Откладывает освобождение `ptr` (как `Box<T>`), пока его кто-то защищает.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` получен из `Box::into_raw`, уже отцеплен от структуры данных
+    /// и не будет отложен повторно.
+    pub unsafe fn retire<T>(&self, ptr: *mut T) {
+        self.retire_with(ptr, |p| drop(Box::from_raw(p)));
+    }
+
+    /// Как [`retire`](Self::retire), но вместо освобождения `Box<T>`
+    /// вызывает `free(ptr)` (например, чтобы вернуть узел в пул).
+    ///
+    /// # Safety
+    ///
+    /// `ptr` уже отцеплен от структуры данных и не будет отложен повторно;
+    /// `free` корректно распоряжается им в любом потоке.
+    pub unsafe fn retire_with<T, F>(&self, ptr: *mut T, free: F)
+    where
+        F: FnOnce(*mut T) + Send + 'static,
+    {
+        self.local().retire(Retired {
+            ptr: ptr as *mut (),
+            free: Deferred::new(move || free(ptr)),
+        });
+    }
+
+    /// Принудительный скан: освобождает всё, что никто не защищает.
+    pub fn scan(&self) {
+        self.local().scan();
+    }
+
+    /// Сколько объектов ждёт освобождения в локальном списке.
+    pub fn pending(&self) -> usize {
+        unsafe { (*self.local().retired.get()).len() }
+    }
+
+    /// Номер слота участника в реестре домена.
+    pub fn index(&self) -> usize {
+        self.local().index
+    }
+
+    /// Домен, в котором зарегистрирован участник.
+    pub fn domain(&self) -> &Domain {
+        &self.local().domain
+    }
+
+    /// HazardPointer, не привязанный к времени жизни дескриптора.
+    ///
+    /// Корректно, так как HazardPointer сам удерживает `Local` через refs.
+    fn hazard_pointer_unbounded(&self) -> HazardPointer<'static> {
+        unsafe { HazardPointer::new(self.local) }
+    }
+
+    /// Как [`hazard_pointer_unbounded`](Self::hazard_pointer_unbounded),
+    /// но без паники: None, если свободных слотов нет.
+    fn try_hazard_pointer_unbounded(&self) -> Option<HazardPointer<'static>> {
+        unsafe { HazardPointer::try_new(self.local) }
+    }
+}
+
+impl Drop for LocalHandle {
+    fn drop(&mut self) {
+        Local::release(self.local);
+    }
+}
+
+/// Один hazard-слот участника. Пока указатель опубликован в слоте,
+/// объект, на который он указывает, не будет освобождён.
+pub struct HazardPointer<'a> {
+    local: *const Local,
+    slot: usize,
+    _marker: PhantomData<&'a LocalHandle>,
+}
+
+impl HazardPointer<'_> {
+    /// # Safety
+    ///
+    /// `local` должен указывать на живой `Local`.
+    unsafe fn new(local: *const Local) -> Self {
+        let slot = (*local).acquire_slot();
+        HazardPointer {
+            local,
+            slot,
+            _marker: PhantomData,
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Как у [`new`](Self::new).
+    unsafe fn try_new(local: *const Local) -> Option<Self> {
+        let slot = (*local).try_acquire_slot()?;
+        Some(HazardPointer {
+            local,
+            slot,
+            _marker: PhantomData,
+        })
+    }
+
+    fn hazard(&self) -> &AtomicPtr<()> {
+        unsafe { &(*self.local).record().hazards[self.slot] }
+    }
+
+    /// Загружает указатель из `src` и защищает его.
+    ///
+    /// Публикуем hazard и перечитываем `src`: если значение не изменилось,
+    /// то в момент публикации объект ещё был достижим, и любой скан после
+    /// этого увидит наш hazard. Иначе повторяем.
+    ///
+    /// Возвращённый указатель (если не null) можно разыменовывать, пока
+    /// слот не перезаписан (`protect`/`reset`) и HazardPointer жив.
+    pub fn protect<T>(&mut self, src: &AtomicPtr<T>) -> *mut T {
+        let hazard = self.hazard();
+        let mut ptr = src.load(Ordering::Acquire);
+        loop {
+            hazard.store(ptr as *mut (), Ordering::SeqCst);
+            atomic::fence(Ordering::SeqCst);
+            let actual = src.load(Ordering::Acquire);
+            if actual == ptr {
+                return ptr;
+            }
+            ptr = actual;
+        }
+    }
+
+    /// Снимает защиту.
+    pub fn reset(&mut self) {
+        self.hazard().store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl Drop for HazardPointer<'_> {
+    fn drop(&mut self) {
+        unsafe { (*self.local).release_slot(self.slot) };
+        Local::release(self.local);
+    }
+}
+
+/// Домен по умолчанию с ленивой (Lazy) инициализацией.
+static DEFAULT_DOMAIN: once_cell::sync::Lazy<Domain> = once_cell::sync::Lazy::new(Domain::new);
+
+// thread_local! хранит Option<LocalHandle> для каждого потока.
+// Если None, значит поток ещё не зарегистрирован в домене по умолчанию.
+thread_local! {
+    static HANDLE: ThreadHandle<LocalHandle> = const { RefCell::new(None) };
+}
+
+/// Домен, которым пользуются свободные функции модуля.
+pub fn default_domain() -> &'static Domain {
+    &DEFAULT_DOMAIN
+}
+
+/// Дескриптор текущего потока в домене по умолчанию.
+fn with_handle<R>(f: impl Fn(&LocalHandle) -> R) -> R {
+    registry::with_thread_handle(&HANDLE, || DEFAULT_DOMAIN.register(), f)
+}
+
+/// Занимает hazard-слот текущего потока в домене по умолчанию.
+///
+/// Если все `HAZARDS_PER_THREAD` слотов потока заняты (вложенные guard'ы),
+/// слот выдаёт новый участник домена: он живёт, пока жив HazardPointer,
+/// и занимает место в реестре (не больше `MAX_THREADS` участников).
+pub fn hazard_pointer() -> HazardPointer<'static> {
+    with_handle(LocalHandle::try_hazard_pointer_unbounded)
+        .unwrap_or_else(|| DEFAULT_DOMAIN.register().hazard_pointer_unbounded())
+}
+
+/// Откладывает освобождение `ptr` в домене по умолчанию.
+///
+/// # Safety
+///
+/// См. [`LocalHandle::retire`].
+pub unsafe fn retire<T>(ptr: *mut T) {
+    with_handle(|h| h.retire(ptr));
+}
+
+/// Как [`retire`], но освобождает через `free(ptr)`.
+///
+/// # Safety
+///
+/// См. [`LocalHandle::retire_with`].
+pub unsafe fn retire_with<T, F>(ptr: *mut T, free: F)
+where
+    F: FnOnce(*mut T) + Send + 'static,
+{
+    // with_handle принимает Fn: отдаём замыкание через Cell ровно один раз
+    let free = Cell::new(Some(free));
+    with_handle(|h| h.retire_with(ptr, free.take().unwrap()));
+}
+
+/// Принудительный скан текущего потока в домене по умолчанию.
+pub fn scan() {
+    with_handle(LocalHandle::scan);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    /// Значение, считающее свои дропы.
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn boxed(drops: &Arc<AtomicUsize>) -> *mut DropCounter {
+        Box::into_raw(Box::new(DropCounter(drops.clone())))
+    }
+
+    #[test]
+    fn test_protected_object_is_not_freed() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let domain = Domain::new();
+        let reader = domain.register();
+        let writer = domain.register();
+
+        let src = AtomicPtr::new(boxed(&drops));
+        let mut hp = reader.hazard_pointer();
+        let p = hp.protect(&src);
+
+        // Писатель отцепляет объект и откладывает его
+        src.store(ptr::null_mut(), Ordering::SeqCst);
+        unsafe { writer.retire(p) };
+        writer.scan();
+        assert_eq!(drops.load(Ordering::Relaxed), 0, "объект под защитой");
+        assert_eq!(writer.pending(), 1);
+
+        hp.reset();
+        writer.scan();
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        assert_eq!(writer.pending(), 0);
+    }
+
+    #[test]
+    fn test_threshold_triggers_scan() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let domain = Domain::with_threshold(4);
+        assert_eq!(domain.threshold(), 4);
+        let handle = domain.register();
+
+        for _ in 0..3 {
+            unsafe { handle.retire(boxed(&drops)) };
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+        unsafe { handle.retire(boxed(&drops)) };
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn test_hazard_slots_are_reused() {
+        let domain = Domain::new();
+        let handle = domain.register();
+        for _ in 0..HAZARDS_PER_THREAD * 3 {
+            let hps: Vec<_> = (0..HAZARDS_PER_THREAD)
+                .map(|_| handle.hazard_pointer())
+                .collect();
+            assert_eq!(hps.len(), HAZARDS_PER_THREAD);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "No free hazard slot")]
+    fn test_too_many_hazard_pointers() {
+        let domain = Domain::new();
+        let handle = domain.register();
+        let _hps: Vec<_> = (0..=HAZARDS_PER_THREAD)
+            .map(|_| handle.hazard_pointer())
+            .collect();
+    }
+
+    #[test]
+    fn test_thread_exit_hands_garbage_to_domain() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let domain = Domain::new();
+        let reader = domain.register();
+        let src = AtomicPtr::new(boxed(&drops));
+        let mut hp = reader.hazard_pointer();
+        let p = hp.protect(&src);
+
+        // Поток откладывает защищённый объект и завершается
+        let (d, addr) = (domain.clone(), p as usize);
+        thread::spawn(move || {
+            let handle = d.register();
+            unsafe { handle.retire(addr as *mut DropCounter) };
+        })
+        .join()
+        .unwrap();
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        // Защита снята — сироту подбирает скан другого участника
+        drop(hp);
+        reader.scan();
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_hazard_pointer_outlives_handle() {
+        let domain = Domain::new();
+        let handle = domain.register();
+        let mut hp = handle.hazard_pointer_unbounded();
+        drop(handle);
+        let src = AtomicPtr::new(ptr::null_mut::<u8>());
+        assert!(hp.protect(&src).is_null());
+        drop(hp);
+
+        let handles: Vec<_> = (0..MAX_THREADS).map(|_| domain.register()).collect();
+        assert_eq!(handles.len(), MAX_THREADS);
+    }
+
+    #[test]
+    fn test_concurrent_swap_and_read() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let domain = Domain::with_threshold(8);
+        let src = Arc::new(AtomicPtr::new(boxed(&drops)));
+        let threads = 4;
+        let iters = 1000;
+
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let (d, src, drops) = (domain.clone(), src.clone(), drops.clone());
+                thread::spawn(move || {
+                    let handle = d.register();
+                    let mut hp = handle.hazard_pointer();
+                    for _ in 0..iters {
+                        // Читаем под защитой
+                        let p = hp.protect(&src);
+                        assert!(Arc::ptr_eq(unsafe { &(*p).0 }, &drops));
+                        hp.reset();
+
+                        // И подменяем, откладывая старый объект
+                        let old = src.swap(boxed(&drops), Ordering::AcqRel);
+                        unsafe { handle.retire(old) };
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        drop(unsafe { Box::from_raw(src.load(Ordering::Relaxed)) });
+        drop(domain);
+        assert_eq!(drops.load(Ordering::Relaxed), threads * iters + 1);
+    }
+
+    #[test]
+    fn test_default_domain() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let src = AtomicPtr::new(boxed(&drops));
+        let mut hp = hazard_pointer();
+        let p = hp.protect(&src);
+        src.store(ptr::null_mut(), Ordering::SeqCst);
+        unsafe { retire(p) };
+        scan();
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        drop(hp);
+        scan();
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        assert_eq!(default_domain().threshold(), DEFAULT_SCAN_THRESHOLD);
+    }
+}
@@ -1,7 +1,18 @@
 pub mod atomic_types;
+pub mod cache_padded;
 pub mod ebr;
+pub mod faa_queue;
+pub mod hazard;
 pub mod lockfree_vs_mutex;
+pub mod ms_queue;
 pub mod ms_queue_crossbeam;
+pub mod pool;
+pub mod qsbr;
+pub mod reclaim;
+mod registry;
 pub mod ring_buffer;
 pub mod stack_and_heap;
+#[cfg(target_pointer_width = "64")]
+pub mod tagged;
 pub mod treiber_stack;
+mod wait_list;
@@ -0,0 +1,21 @@
+//! Michael-Scott Queue на собственном EBR крейта ([`crate::ebr`]).
+//!
+//! Это очередь из [`crate::ms_queue_crossbeam`] со схемой освобождения
+//! [`Ebr`]: отцеплённые узлы откладываются в коллектор по умолчанию
+//! ([`crate::ebr::pin`]), и внешняя зависимость `crossbeam_epoch` не нужна.
+//! Создаётся так же: `MSQueue::new()` или `MSQueue::builder()`.
+
+use crate::ms_queue_crossbeam;
+use crate::reclaim::Ebr;
+
+/// Michael-Scott Queue поверх [`crate::ebr`].
+pub type MSQueue<T> = ms_queue_crossbeam::MSQueue<T, Ebr>;
+
+/// Билдер [`MSQueue`]: пул узлов и счётчики.
+pub type MSQueueBuilder<T> = ms_queue_crossbeam::MSQueueBuilder<T, Ebr>;
+
+/// Future из [`MSQueue::pop_async`].
+pub type PopAsync<'a, T> = ms_queue_crossbeam::PopAsync<'a, T, Ebr>;
+
+/// [`MSQueue`] с чтением без извлечения.
+pub type PeekableQueue<T> = ms_queue_crossbeam::PeekableQueue<T, Ebr>;
@@ -1,79 +1,226 @@
-use crossbeam_epoch::{self as epoch, Atomic, Owned, Shared};
-use std::sync::atomic::Ordering;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use crate::cache_padded::CachePadded;
+use crate::pool::{NodePool, PoolConfig};
+use crate::reclaim::{CrossbeamEpoch, Ebr, EpochReclaimer, Reclaimer};
+use crate::wait_list::{WaitId, WaitList};
 
 /// Узел очереди (каждый узел хранит:
-///  - data: Option<T> (None у фиктивного узла),
+///  - data: значение (не инициализировано у фиктивного узла),
 ///  - next: атомарный указатель на следующий узел).
+///
+/// Значение инициализировано у узлов после head: pop забирает его
+/// побитовым чтением. У [`PeekableQueue`] pop отдаёт клон, а значение
+/// остаётся в узле, ставшем dummy, и дропается вместе с ним — так
+/// конкурентный [`PeekableQueue::peek_with`] не видит забранного значения.
 struct Node<T> {
-    data: Option<T>,
-    next: Atomic<Node<T>>,
+    data: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
 }
 
 impl<T> Node<T> {
     /// Конструктор узла с реальным значением
     fn new(data: T) -> Self {
         Self {
-            data: Some(data),
-            next: Atomic::null(),
+            data: MaybeUninit::new(data),
+            next: AtomicPtr::new(ptr::null_mut()),
         }
     }
 
-    /// Создаём «фиктивный» (dummy) узел, у которого data == None.
+    /// Создаём «фиктивный» (dummy) узел без значения.
     /// Он используется в начале очереди (head = tail = dummy).
     fn dummy() -> Self {
         Self {
-            data: None,
-            next: Atomic::null(),
+            data: MaybeUninit::uninit(),
+            next: AtomicPtr::new(ptr::null_mut()),
         }
     }
 }
 
 /// Michael-Scott Queue
 ///
-///  - `head`: указывает на первый узел (Atomic<Node<T>>)  
+///  - `head`: указывает на первый узел
 ///  - `tail`: указывает на последний узел
 ///
 /// Изначально head=tail указывают на dummy-узел.
-#[derive(Default)]
-pub struct MSQueue<T> {
-    head: Atomic<Node<T>>,
-    tail: Atomic<Node<T>>,
+///
+/// Схема освобождения памяти задаётся параметром `R` (см. [`crate::reclaim`]);
+/// по умолчанию — `crossbeam_epoch`.
+///
+/// Пул узлов (см. [`MSQueue::builder`]) убирает аллокацию из push:
+/// узел берётся из кэша потока, а отцеплённый pop'ом старый head
+/// возвращается в пул, когда схема `R` признает его никому не видимым.
+///
+/// `head` и `tail` лежат в разных кэш-линиях ([`CachePadded`]): иначе
+/// производители и потребители выбивали бы линию друг у друга.
+pub struct MSQueue<T, R: Reclaimer = CrossbeamEpoch> {
+    head: CachePadded<AtomicPtr<Node<T>>>,
+    tail: CachePadded<AtomicPtr<Node<T>>>,
+    pool: Option<Arc<NodePool>>,
+    counters: Option<Counters>,
+    waiters: WaitList,
+    // Есть у очередей с peek (см. MSQueueBuilder::peekable): pop отдаёт
+    // клон, а значение в узле дропается вместе с узлом
+    clone_on_pop: Option<fn(&T) -> T>,
+    // Исходный dummy, пока его не сняли: единственный узел без значения
+    // у очереди с peek
+    blank: AtomicPtr<Node<T>>,
+    _marker: PhantomData<(*mut T, R)>, // Send/Sync задаём вручную ниже
 }
 
+/// Счётчики вставок и извлечений для приблизительного [`MSQueue::len`].
+/// Каждый в своей кэш-линии: их крутят производители и потребители.
+struct Counters {
+    enqueued: CachePadded<AtomicUsize>,
+    dequeued: CachePadded<AtomicUsize>,
+}
+
+// Значения переходят между потоками через очередь, но общих ссылок
+// на них очередь не раздаёт — достаточно T: Send.
+unsafe impl<T: Send, R: Reclaimer> Send for MSQueue<T, R> {}
+unsafe impl<T: Send, R: Reclaimer> Sync for MSQueue<T, R> {}
+
 impl<T> MSQueue<T> {
-    /// Создаём новую очередь: head и tail ссылаются на фиктивный (dummy) узел.
+    /// Создаём новую очередь поверх `crossbeam_epoch`.
+    /// Для другой схемы: `MSQueue::<T, R>::default()`.
+    ///
+    /// `new` есть и у `MSQueue<T, Ebr>` (см. [`crate::ms_queue`]), поэтому,
+    /// если тип очереди не выводится из контекста, укажите `T`:
+    /// `MSQueue::<T>::new()`.
     pub fn new() -> Self {
-        let guard = &epoch::pin();
-        // "pin()" означает, что мы входим в критическую секцию Epoch.
+        Self::default()
+    }
 
-        // Создаём dummy-узел в куче.
-        let dummy = Owned::new(Node::dummy()).into_shared(guard); // Превращаем Owned в Shared под управлением Epoch
+    /// Билдер очереди: схема освобождения памяти и пул узлов.
+    pub fn builder() -> MSQueueBuilder<T> {
+        MSQueueBuilder::new()
+    }
+}
+
+/// Конструкторы очереди на EBR крейта — для [`crate::ms_queue::MSQueue`].
+impl<T> MSQueue<T, Ebr> {
+    /// Создаём новую очередь поверх [`crate::ebr`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Билдер очереди на [`crate::ebr`]: пул узлов, счётчики, peek.
+    pub fn builder() -> MSQueueBuilder<T, Ebr> {
+        MSQueueBuilder::default()
+    }
+}
+
+/// Создаём новую очередь: head и tail ссылаются на фиктивный (dummy) узел.
+impl<T, R: Reclaimer> Default for MSQueue<T, R> {
+    fn default() -> Self {
+        Self::with_options(None, false, None)
+    }
+}
+
+impl<T, R: Reclaimer> MSQueue<T, R> {
+    fn with_options(
+        pool: Option<PoolConfig>,
+        counters: bool,
+        clone_on_pop: Option<fn(&T) -> T>,
+    ) -> Self {
+        let pool = pool.map(|config| Arc::new(NodePool::new::<Node<T>>(config)));
+        // Создаём dummy-узел в куче (память из пула совместима с Box).
+        // Пока очередь никому не видна, защищать его не нужно.
+        let dummy = Box::into_raw(Box::new(Node::dummy()));
 
         MSQueue {
             // Инициализируем head и tail указателями на dummy-узел
-            head: Atomic::from(dummy),
-            tail: Atomic::from(dummy),
+            head: CachePadded::new(AtomicPtr::new(dummy)),
+            tail: CachePadded::new(AtomicPtr::new(dummy)),
+            pool,
+            counters: counters.then(|| Counters {
+                enqueued: CachePadded::new(AtomicUsize::new(0)),
+                dequeued: CachePadded::new(AtomicUsize::new(0)),
+            }),
+            waiters: WaitList::default(),
+            clone_on_pop,
+            blank: AtomicPtr::new(dummy),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Сколько свободных узлов лежит в пуле (0, если пул выключен).
+    pub fn pooled(&self) -> usize {
+        self.pool.as_ref().map_or(0, |pool| pool.pooled())
+    }
+
+    /// Новый узел — из пула, если он включён.
+    fn alloc_node(&self, data: T) -> *mut Node<T> {
+        match &self.pool {
+            Some(pool) => pool.alloc(Node::new(data)),
+            None => Box::into_raw(Box::new(Node::new(data))),
+        }
+    }
+
+    /// Отдаёт отцеплённый старый head схеме `R`: освобождение или возврат
+    /// в пул. У очереди с peek значение в нём дропается тогда же.
+    ///
+    /// # Safety
+    ///
+    /// Как у [`Reclaimer::retire`]; `node` снят с head нами.
+    unsafe fn retire_node(&self, guard: &R::Guard, node: *mut Node<T>) {
+        let drop_value = self.clone_on_pop.is_some() && !self.take_blank(node);
+        match &self.pool {
+            Some(pool) => {
+                let pool = Arc::clone(pool);
+                R::retire_with(guard, node, move |p| unsafe {
+                    if drop_value {
+                        (*p).data.assume_init_drop();
+                    }
+                    pool.recycle(p)
+                });
+            }
+            None if drop_value => R::retire_with(guard, node, |p| unsafe {
+                Box::from_raw(p).data.assume_init_drop()
+            }),
+            None => R::retire(guard, node),
         }
     }
 
+    /// Был ли снятый нами head исходным dummy (без значения).
+    fn take_blank(&self, node: *mut Node<T>) -> bool {
+        // Исходный dummy снимают ровно один раз, и его адрес не вернётся
+        // из пула раньше, чем мы обнулим blank
+        if self.blank.load(Ordering::Relaxed) != node {
+            return false;
+        }
+        self.blank.store(ptr::null_mut(), Ordering::Relaxed);
+        true
+    }
+
     /// Помещаем (enqueue) элемент в конец очереди.
     /// Реализуется классической MS-Queue логикой: пытаемся
     /// «приделать» новый узел к `tail.next`.
     pub fn push(&self, data: T) {
-        let guard = &epoch::pin();
+        let mut guard = R::pin();
         // Каждый раз при работе с Queue мы входим в критическую секцию (pin).
 
-        // Создаём новый узел в куче, сразу переводим Owned -> Shared.
-        let new_node = Owned::new(Node::new(data)).into_shared(guard);
+        // Создаём новый узел в куче. До публикации он принадлежит только нам.
+        let new_node = self.alloc_node(data);
 
         loop {
-            // Читаем текущий tail
-            let tail = self.tail.load(Ordering::Acquire, guard);
-            let tail_ref = unsafe { tail.deref() };
+            // Читаем и защищаем текущий tail
+            let tail = R::protect(&mut guard, 0, &self.tail);
+            let tail_ref = unsafe { &*tail };
             // tail_ref — это «разыменованный» узел tail
 
             // Смотрим tail_ref.next (указатель на следующий узел).
-            let next = tail_ref.next.load(Ordering::Acquire, guard);
+            // Сам next не разыменовываем, поэтому защищать его не нужно.
+            let next = tail_ref.next.load(Ordering::Acquire);
 
             if !next.is_null() {
                 // Если next не пуст, значит кто-то уже добавил новый узел,
@@ -83,7 +230,6 @@ impl<T> MSQueue<T> {
                     next,
                     Ordering::Release,
                     Ordering::Relaxed,
-                    guard,
                 );
                 // Идём заново в loop.
                 continue;
@@ -94,23 +240,26 @@ impl<T> MSQueue<T> {
             if tail_ref
                 .next
                 .compare_exchange_weak(
-                    Shared::null(), // Ожидаем, что там null
-                    new_node,       // хотим поставить new_node
+                    ptr::null_mut(), // Ожидаем, что там null
+                    new_node,        // хотим поставить new_node
                     Ordering::Release,
                     Ordering::Relaxed,
-                    guard,
                 )
                 .is_ok()
             {
                 // Успешно прицепили new_node к tail.next.
+                if let Some(counters) = &self.counters {
+                    counters.enqueued.fetch_add(1, Ordering::Relaxed);
+                }
                 // Теперь пытаемся сдвинуть tail на new_node (не критично, если не выйдет).
                 let _ = self.tail.compare_exchange_weak(
                     tail,
                     new_node,
                     Ordering::Release,
                     Ordering::Relaxed,
-                    guard,
                 );
+                // Если никто не ждёт — забор и одно чтение счётчика
+                self.waiters.notify_if_waiting();
                 return; // Завершаем push.
             }
             // Если compare_exchange не сработал, значит кто-то нас опередил, повторяем loop.
@@ -120,58 +269,336 @@ impl<T> MSQueue<T> {
     /// Извлекаем (dequeue) элемент из головы.
     /// Возвращаем Some(T), если очередь не пуста, или None, если пуста.
     pub fn pop(&self) -> Option<T> {
-        let guard = &epoch::pin();
+        let mut guard = R::pin();
 
         loop {
-            // Загружаем head
-            let head = self.head.load(Ordering::Acquire, guard);
-            let head_ref = unsafe { head.deref() };
+            // Загружаем и защищаем head
+            let head = R::protect(&mut guard, 0, &self.head);
+            let head_ref = unsafe { &*head };
 
             // Смотрим head_ref.next — если пуст, значит очередь пуста.
-            let next = head_ref.next.load(Ordering::Acquire, guard);
+            let next = R::protect(&mut guard, 1, &head_ref.next);
 
             if next.is_null() {
                 return None;
             }
 
+            // Пока head не сдвинулся, next не мог быть отложен на удаление:
+            // для hazard pointers это и делает защиту next действительной.
+            if self.head.load(Ordering::Acquire) != head {
+                continue;
+            }
+
+            // head не должен обгонять tail: иначе tail укажет на уже
+            // отложенный узел. Сначала помогаем сдвинуть отстающий tail.
+            let tail = self.tail.load(Ordering::Acquire);
+            if tail == head {
+                let _ = self.tail.compare_exchange_weak(
+                    tail,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                );
+                continue;
+            }
+
             // Если next не пуст, пробуем сдвинуть head → next
             if self
                 .head
-                .compare_exchange_weak(head, next, Ordering::Release, Ordering::Relaxed, guard)
+                .compare_exchange_weak(head, next, Ordering::Release, Ordering::Relaxed)
                 .is_ok()
             {
-                // Успешно поменяли head.
-                // Узел head больше не нужен, откладываем его удаление.
-                let next_ptr = next.as_raw() as *mut Node<T>;
-                // Получаем мутабельную ссылку
-                let next_ref = unsafe { &mut *next_ptr };
-                // Забираем данные (Some(T)) из нового head
-                let data = next_ref.data.take();
+                // Успешно поменяли head. Только мы можем забрать данные
+                // нового head (теперь это dummy).
+                let data = match self.clone_on_pop {
+                    // peek_with может ещё смотреть на значение: отдаём клон,
+                    // а само значение дропнется вместе с узлом
+                    Some(clone) => clone(unsafe { &*(*next).data.as_ptr() }),
+                    None => unsafe { ptr::read((*next).data.as_ptr()) },
+                };
+                if let Some(counters) = &self.counters {
+                    counters.dequeued.fetch_add(1, Ordering::Relaxed);
+                }
 
                 // Откладываем освобождение старого head:
-                unsafe {
-                    guard.defer_destroy(head);
-                }
+                unsafe { self.retire_node(&guard, head) };
 
-                return data;
+                return Some(data);
             }
             // Если compare_exchange не сработал, значит head поменялся, повторяем loop.
         }
     }
 }
 
-/// Drop-логика: очищаем все элементы из очереди, пока есть.
-/// Затем удаляем dummy-узел через `defer_destroy`.
-impl<T> Drop for MSQueue<T> {
+impl<T, R: Reclaimer> MSQueue<T, R> {
+    /// Пуста ли очередь в момент вызова (ничего не меняет).
+    pub fn is_empty(&self) -> bool {
+        let mut guard = R::pin();
+        let head = R::protect(&mut guard, 0, &self.head);
+        // head защищён; за ним нет узла — очередь пуста
+        unsafe { (*head).next.load(Ordering::Acquire) }.is_null()
+    }
+
+    /// Приблизительная длина: разность счётчиков вставок и извлечений.
+    /// `None`, если счётчики не включены (см. [`MSQueueBuilder::counters`]).
+    ///
+    /// Счётчики обновляются после самой операции, так что при конкурентных
+    /// push/pop значение может ненадолго отставать в любую сторону.
+    pub fn len(&self) -> Option<usize> {
+        let counters = self.counters.as_ref()?;
+        let dequeued = counters.dequeued.load(Ordering::Relaxed);
+        let enqueued = counters.enqueued.load(Ordering::Relaxed);
+        Some(enqueued.saturating_sub(dequeued))
+    }
+}
+
+/// Ожидающие потребители: список ждущих сбоку от очереди (модуль `wait_list`).
+impl<T, R: Reclaimer> MSQueue<T, R> {
+    /// Извлекает элемент, при пустой очереди паркуя поток до push.
+    pub fn pop_blocking(&self) -> T {
+        loop {
+            if let Some(data) = self.pop_or_park(None) {
+                return data;
+            }
+        }
+    }
+
+    /// Как [`pop_blocking`](Self::pop_blocking), но ждёт не дольше `timeout`.
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if let Some(data) = self.pop_or_park(Some(deadline)) {
+                return Some(data);
+            }
+        }
+        self.pop()
+    }
+
+    /// Future, который завершается первым извлечённым элементом.
+    /// Отменённый (дропнутый) future не теряет адресованное ему пробуждение.
+    pub fn pop_async(&self) -> PopAsync<'_, T, R> {
+        PopAsync {
+            queue: self,
+            id: None,
+        }
+    }
+
+    /// Одна попытка: pop, иначе регистрация, повторный pop и парковка
+    /// до пробуждения или `deadline`.
+    fn pop_or_park(&self, deadline: Option<Instant>) -> Option<T> {
+        if let Some(data) = self.pop() {
+            return Some(data);
+        }
+        let id = self.waiters.register_thread();
+        // Повторная проверка после регистрации: push мог успеть до неё
+        if let Some(data) = self.pop() {
+            self.waiters.unregister(id);
+            return Some(data);
+        }
+        if !self.waiters.park(id, deadline) {
+            // Таймаут; если пробуждение пришло в последний момент — передаём
+            self.waiters.unregister(id);
+        }
+        None
+    }
+}
+
+/// Future из [`MSQueue::pop_async`].
+pub struct PopAsync<'a, T, R: Reclaimer = CrossbeamEpoch> {
+    queue: &'a MSQueue<T, R>,
+    /// Запись в списке ждущих, если задача зарегистрирована.
+    id: Option<WaitId>,
+}
+
+impl<T, R: Reclaimer> Future for PopAsync<'_, T, R> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let waiters = &self.queue.waiters;
+        loop {
+            // Запись удалена — нас разбудили, регистрация израсходована
+            if let Some(id) = self.id {
+                if !waiters.is_waiting(id) {
+                    self.id = None;
+                }
+            }
+
+            if let Some(data) = self.queue.pop() {
+                if let Some(id) = self.id.take() {
+                    waiters.unregister(id);
+                }
+                return Poll::Ready(data);
+            }
+
+            match self.id {
+                // Уже зарегистрированы и перепроверили очередь — обновляем waker
+                Some(id) => match waiters.register_task(Some(id), cx.waker()) {
+                    Some(_) => return Poll::Pending,
+                    None => self.id = None, // Разбудили между проверками
+                },
+                // Регистрируемся и перепроверяем очередь на следующем круге
+                None => self.id = waiters.register_task(None, cx.waker()),
+            }
+        }
+    }
+}
+
+impl<T, R: Reclaimer> Drop for PopAsync<'_, T, R> {
     fn drop(&mut self) {
-        // Пока есть элементы, достаём их
-        while self.pop().is_some() {}
-
-        let guard = &epoch::pin();
-        // Освобождаем dummy-узел
-        let head = self.head.load(Ordering::Relaxed, guard);
-        unsafe {
-            guard.defer_destroy(head);
+        if let Some(id) = self.id.take() {
+            self.queue.waiters.unregister(id);
+        }
+    }
+}
+
+/// [`MSQueue`] с чтением без извлечения (см. [`MSQueueBuilder::peekable`]).
+///
+/// pop отдаёт клон, а значение остаётся в узле и дропается вместе с ним,
+/// когда на узел уже никто не смотрит, — поэтому [`peek_with`](Self::peek_with)
+/// работает с любым `T: Clone`. Остальные операции — как у [`MSQueue`]
+/// (через `Deref`).
+pub struct PeekableQueue<T, R: Reclaimer = CrossbeamEpoch> {
+    queue: MSQueue<T, R>,
+}
+
+impl<T, R: Reclaimer> Deref for PeekableQueue<T, R> {
+    type Target = MSQueue<T, R>;
+
+    fn deref(&self) -> &MSQueue<T, R> {
+        &self.queue
+    }
+}
+
+/// Чтение без извлечения — только для схем на эпохах: hazard pointers
+/// не удерживают узел на всё время `f`. `T: Sync` — потому что одно
+/// значение читают несколько потоков.
+impl<T: Sync, R: EpochReclaimer> PeekableQueue<T, R> {
+    /// Вызывает `f` для первого элемента очереди, не извлекая его.
+    /// `None`, если очередь пуста.
+    pub fn peek_with<U>(&self, f: impl FnOnce(&T) -> U) -> Option<U> {
+        let mut guard = R::pin();
+        let head = R::protect(&mut guard, 0, &self.queue.head);
+        let next = unsafe { (*head).next.load(Ordering::Acquire) };
+        if next.is_null() {
+            return None;
+        }
+        // next был достижим после pin и не освободится, пока жив guard;
+        // его значение инициализировано push'ем, а pop оставляет его в узле
+        Some(f(unsafe { &*(*next).data.as_ptr() }))
+    }
+}
+
+/// Drop-логика: `&mut self` гарантирует, что других потоков нет,
+/// поэтому ни pin, ни отложенное освобождение не нужны — проходим цепочку
+/// от dummy-узла и освобождаем узлы сразу, дропая на месте значения
+/// всех узлов после dummy.
+///
+/// Узлы, снятые раньше, уже отданы схеме `R` и в цепочку не входят.
+impl<T, R: Reclaimer> Drop for MSQueue<T, R> {
+    fn drop(&mut self) {
+        let mut cur = *self.head.get_mut();
+        // У очереди с peek значение dummy ещё в нём (если это не исходный)
+        let mut empty = self.clone_on_pop.is_none() || cur == *self.blank.get_mut();
+        while !cur.is_null() {
+            // Память узлов из пула совместима с Box<Node<T>>
+            let mut node = unsafe { Box::from_raw(cur) };
+            if !empty {
+                unsafe { node.data.assume_init_drop() };
+            }
+            empty = false;
+            cur = *node.next.get_mut();
+        }
+    }
+}
+
+/// Билдер [`MSQueue`]: схема освобождения памяти и пул узлов.
+///
+/// Например: `MSQueue::<u64>::builder().reclaimer::<Ebr>().thread_cache(128).build()`.
+pub struct MSQueueBuilder<T, R: Reclaimer = CrossbeamEpoch> {
+    pool: Option<PoolConfig>,
+    counters: bool,
+    _marker: PhantomData<fn() -> (T, R)>,
+}
+
+impl<T> MSQueueBuilder<T> {
+    /// Билдер с настройками по умолчанию: `crossbeam_epoch`, без пула.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// derive добавил бы лишние ограничения на T и R.
+impl<T, R: Reclaimer> Clone for MSQueueBuilder<T, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, R: Reclaimer> Copy for MSQueueBuilder<T, R> {}
+
+impl<T, R: Reclaimer> Default for MSQueueBuilder<T, R> {
+    fn default() -> Self {
+        MSQueueBuilder {
+            pool: None,
+            counters: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, R: Reclaimer> MSQueueBuilder<T, R> {
+    /// Другая схема освобождения памяти (см. [`crate::reclaim`]).
+    pub fn reclaimer<R2: Reclaimer>(self) -> MSQueueBuilder<T, R2> {
+        MSQueueBuilder {
+            pool: self.pool,
+            counters: self.counters,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Включает пул узлов с настройками `config`.
+    pub fn pool(mut self, config: PoolConfig) -> Self {
+        self.pool = Some(config);
+        self
+    }
+
+    /// Включает пул узлов и задаёт размер кэша потока.
+    pub fn thread_cache(self, n: usize) -> Self {
+        let config = self.pool.unwrap_or_default();
+        self.pool(PoolConfig {
+            thread_cache: n,
+            ..config
+        })
+    }
+
+    /// Включает пул узлов и ограничивает общий пул `n` узлами.
+    pub fn max_pooled(self, n: usize) -> Self {
+        let config = self.pool.unwrap_or_default();
+        self.pool(PoolConfig {
+            max_pooled: n,
+            ..config
+        })
+    }
+
+    /// Включает счётчики вставок и извлечений для [`MSQueue::len`]
+    /// (по два атомарных инкремента на пару push/pop).
+    pub fn counters(mut self) -> Self {
+        self.counters = true;
+        self
+    }
+
+    /// Создаёт пустую очередь.
+    pub fn build(self) -> MSQueue<T, R> {
+        MSQueue::with_options(self.pool, self.counters, None)
+    }
+}
+
+impl<T: Clone, R: Reclaimer> MSQueueBuilder<T, R> {
+    /// Создаёт пустую очередь с [`peek_with`](PeekableQueue::peek_with).
+    /// Цена — клон на каждый pop.
+    pub fn peekable(self) -> PeekableQueue<T, R> {
+        PeekableQueue {
+            queue: MSQueue::with_options(self.pool, self.counters, Some(T::clone)),
         }
     }
 }
@@ -180,160 +607,567 @@ impl<T> Drop for MSQueue<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::reclaim::{Ebr, Hazard};
     use std::sync::Arc;
     use std::thread;
-    use std::time::Duration;
 
-    /// 1. Проверка, что очередь пуста по умолчанию
-    #[test]
-    fn test_empty_queue() {
-        let q: MSQueue<i32> = MSQueue::new();
-        assert_eq!(q.pop(), None, "Новая очередь должна быть пустой");
-    }
+    /// Общий набор тестов MSQueue, не зависящий от схемы освобождения.
+    ///
+    /// Разворачивается в подмодуль `$name`, где `MSQueue<T>` — очередь
+    /// со схемой `$R`, а `builder()` — её билдер.
+    /// `flush` — функция, после которой отложенный мусор схемы освобождён.
+    macro_rules! ms_queue_tests {
+        (mod $name:ident, reclaimer: $R:ty, flush: $flush:expr) => {
+            mod $name {
+                use super::*;
+                use std::sync::atomic::{AtomicUsize, Ordering};
+                use std::sync::{Arc, Mutex};
+                use std::thread;
+                use std::time::{Duration, Instant};
 
-    /// 2. Тест, что мы можем добавить и убрать один элемент
-    #[test]
-    fn test_single_element() {
-        let q: MSQueue<i32> = MSQueue::new();
-        q.push(42);
-        assert_eq!(q.pop(), Some(42));
-        assert_eq!(q.pop(), None);
-    }
+                /// Значение, считающее свои дропы.
+                struct DropCounter(Arc<AtomicUsize>);
 
-    /// 3. Последовательные операции в одном потоке
-    #[test]
-    fn test_sequential_operations_single_thread() {
-        let q: MSQueue<i32> = MSQueue::new();
+                impl Drop for DropCounter {
+                    fn drop(&mut self) {
+                        self.0.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
 
-        // Добавляем три элемента
-        q.push(10);
-        q.push(20);
-        q.push(30);
+                type MSQueue<T> = crate::ms_queue_crossbeam::MSQueue<T, $R>;
 
-        // Извлекаем два
-        assert_eq!(q.pop(), Some(10));
-        assert_eq!(q.pop(), Some(20));
+                fn builder<T>() -> MSQueueBuilder<T, $R> {
+                    MSQueueBuilder::default()
+                }
+
+                /// 1. Проверка, что очередь пуста по умолчанию
+                #[test]
+                fn test_empty_queue() {
+                    let q: MSQueue<i32> = MSQueue::default();
+                    assert_eq!(q.pop(), None, "Новая очередь должна быть пустой");
+                }
 
-        // Добавляем ещё
-        q.push(40);
+                /// 2. Тест, что мы можем добавить и убрать один элемент
+                #[test]
+                fn test_single_element() {
+                    let q: MSQueue<i32> = MSQueue::default();
+                    q.push(42);
+                    assert_eq!(q.pop(), Some(42));
+                    assert_eq!(q.pop(), None);
+                }
 
-        // Проверяем остатки
-        assert_eq!(q.pop(), Some(30));
-        assert_eq!(q.pop(), Some(40));
-        assert_eq!(q.pop(), None); // Пусто
-    }
+                /// 3. Последовательные операции в одном потоке
+                #[test]
+                fn test_sequential_operations_single_thread() {
+                    let q: MSQueue<i32> = MSQueue::default();
 
-    /// 4. Многопоточность: 2 производителя, 2 потребителя
-    #[test]
-    fn test_2_producers_2_consumers() {
-        let q = Arc::new(MSQueue::new());
-        let mut handles = vec![];
-
-        // Два производителя
-        for i in 0..2 {
-            let qc = Arc::clone(&q);
-            handles.push(thread::spawn(move || {
-                for j in 0..100 {
-                    qc.push(i * 100 + j);
+                    // Добавляем три элемента
+                    q.push(10);
+                    q.push(20);
+                    q.push(30);
+
+                    // Извлекаем два
+                    assert_eq!(q.pop(), Some(10));
+                    assert_eq!(q.pop(), Some(20));
+
+                    // Добавляем ещё
+                    q.push(40);
+
+                    // Проверяем остатки
+                    assert_eq!(q.pop(), Some(30));
+                    assert_eq!(q.pop(), Some(40));
+                    assert_eq!(q.pop(), None); // Пусто
                 }
-            }));
-        }
 
-        // Два потребителя
-        let results = Arc::new(std::sync::Mutex::new(Vec::new()));
-        for _ in 0..2 {
-            let qc = Arc::clone(&q);
-            let res = Arc::clone(&results);
-            handles.push(thread::spawn(move || {
-                loop {
-                    if let Some(val) = qc.pop() {
-                        res.lock().unwrap().push(val);
-                    } else {
-                        // Если очередь пуста, подождём
-                        thread::sleep(Duration::from_micros(50));
-                        // Проверяем, достигли ли мы 200
-                        if res.lock().unwrap().len() >= 200 {
-                            break;
+                /// 4. Многопоточность: 2 производителя, 2 потребителя
+                #[test]
+                fn test_2_producers_2_consumers() {
+                    let q = Arc::new(MSQueue::default());
+                    let mut handles = vec![];
+
+                    // Два производителя
+                    for i in 0..2 {
+                        let qc = Arc::clone(&q);
+                        handles.push(thread::spawn(move || {
+                            for j in 0..100 {
+                                qc.push(i * 100 + j);
+                            }
+                        }));
+                    }
+
+                    // Два потребителя: каждый ждёт ровно 100 элементов, без опроса в цикле
+                    let results = Arc::new(Mutex::new(Vec::new()));
+                    for _ in 0..2 {
+                        let qc = Arc::clone(&q);
+                        let res = Arc::clone(&results);
+                        handles.push(thread::spawn(move || {
+                            for _ in 0..100 {
+                                let val = qc.pop_blocking();
+                                res.lock().unwrap().push(val);
+                            }
+                        }));
+                    }
+
+                    // Ждём все потоки
+                    for h in handles {
+                        h.join().unwrap();
+                    }
+
+                    let mut final_vec = results.lock().unwrap().clone();
+                    final_vec.sort();
+                    assert_eq!(final_vec.len(), 200);
+                    for (i, val) in final_vec.iter().enumerate().take(200) {
+                        assert_eq!(*val, i as i32);
+                    }
+                    assert!(q.is_empty());
+                }
+
+                /// 5. Проверяем mix: 4 потока, в каждом чередуется push/pop
+                #[test]
+                fn test_mix_push_pop() {
+                    let q = Arc::new(MSQueue::default());
+                    let threads = 4;
+                    let iters = 500;
+                    let mut handles = vec![];
+
+                    // Каждый поток: в цикле (push + pop), многократно
+                    for t in 0..threads {
+                        let qc = Arc::clone(&q);
+                        handles.push(thread::spawn(move || {
+                            let start = t * 1000;
+                            for i in 0..iters {
+                                qc.push(start + i);
+                                let _ = qc.pop();
+                            }
+                        }));
+                    }
+
+                    for h in handles {
+                        h.join().unwrap();
+                    }
+
+                    // Сколько осталось — не знаем, но проверяем, что программа не падает
+                    let mut values = Vec::new();
+                    while let Some(x) = q.pop() {
+                        values.push(x);
+                    }
+                    // Можно сделать какие-то asserts, но результат не гарантирован
+                    // (так как concurrent).
+                }
+
+                /// 6. Stress-тест: много push/pop подряд разными потоками
+                #[test]
+                fn test_stress() {
+                    let q = Arc::new(MSQueue::default());
+
+                    let thread_count = 8;
+                    let per_thread = 2000;
+                    let mut handles = vec![];
+
+                    for _ in 0..thread_count {
+                        let qc = Arc::clone(&q);
+                        handles.push(thread::spawn(move || {
+                            for i in 0..per_thread {
+                                qc.push(i);
+                                // Сразу пытаемся pop()
+                                let _ = qc.pop();
+                            }
+                        }));
+                    }
+
+                    for h in handles {
+                        h.join().unwrap();
+                    }
+
+                    let mut leftover = 0;
+                    while let Some(_val) = q.pop() {
+                        leftover += 1;
+                    }
+                    println!("Осталось {} элементов после stress-теста.", leftover);
+                }
+
+                /// 7. Значения доставлены ровно один раз — с пулом узлов и без
+                #[test]
+                fn test_all_values_delivered() {
+                    fn check(q: MSQueue<usize>) {
+                        let q = Arc::new(q);
+                        let threads = 4;
+                        let per_thread = 1000;
+
+                        let handles: Vec<_> = (0..threads)
+                            .map(|t| {
+                                let qc = Arc::clone(&q);
+                                thread::spawn(move || {
+                                    let mut popped = Vec::new();
+                                    for i in 0..per_thread {
+                                        qc.push(t * per_thread + i);
+                                        popped.extend(qc.pop());
+                                    }
+                                    popped
+                                })
+                            })
+                            .collect();
+
+                        let mut values: Vec<_> = handles
+                            .into_iter()
+                            .flat_map(|h| h.join().unwrap())
+                            .collect();
+                        while let Some(x) = q.pop() {
+                            values.push(x);
                         }
+                        values.sort();
+                        assert_eq!(values, (0..threads * per_thread).collect::<Vec<_>>());
                     }
+
+                    check(MSQueue::default());
+                    check(builder().thread_cache(16).max_pooled(64).build());
                 }
-            }));
-        }
 
-        // Ждём все потоки
-        for h in handles {
-            h.join().unwrap();
-        }
+                /// 8. Значения, оставшиеся в очереди с пулом, дропаются вместе с ней
+                #[test]
+                fn test_pooled_drop_drops_values() {
+                    let value = Arc::new(());
+                    let q = builder().thread_cache(4).build();
+                    for _ in 0..5 {
+                        q.push(Arc::clone(&value));
+                    }
+                    drop(q.pop());
+                    drop(q);
+                    // Оставшиеся значения дропаются прямо в Drop, без схемы освобождения
+                    assert_eq!(Arc::strong_count(&value), 1);
+                }
 
-        let mut final_vec = results.lock().unwrap().clone();
-        final_vec.sort();
-        assert_eq!(final_vec.len(), 200);
-        for (i, val) in final_vec.iter().enumerate().take(200) {
-            assert_eq!(*val, i as i32);
-        }
+                /// 9. Каждое значение дропается ровно один раз
+                // Снятые — у вызывающего, оставшиеся — в Drop очереди;
+                // отложенные узлы значений уже не держат.
+                #[test]
+                fn test_drop_each_value_once() {
+                    let drops = Arc::new(AtomicUsize::new(0));
+                    let q = MSQueue::default();
+                    for _ in 0..10 {
+                        q.push(DropCounter(Arc::clone(&drops)));
+                    }
+                    for _ in 0..3 {
+                        drop(q.pop());
+                    }
+                    assert_eq!(drops.load(Ordering::Relaxed), 3);
+
+                    drop(q);
+                    assert_eq!(drops.load(Ordering::Relaxed), 10);
+                    for _ in 0..10 {
+                        $flush();
+                    }
+                    assert_eq!(drops.load(Ordering::Relaxed), 10);
+                }
+
+                /// 10. Пустая очередь: Drop освобождает только dummy
+                #[test]
+                fn test_drop_empty_queue() {
+                    let q: MSQueue<DropCounter> = MSQueue::default();
+                    drop(q);
+                    let q = MSQueue::<String>::default();
+                    q.push("x".to_string());
+                    assert_eq!(q.pop().as_deref(), Some("x"));
+                    drop(q);
+                }
+
+                /// 11. Счётчики: len есть только со включёнными счётчиками
+                #[test]
+                fn test_len_with_counters() {
+                    let q: MSQueue<u32> = MSQueue::default();
+                    q.push(1);
+                    assert_eq!(q.len(), None);
+
+                    let q = builder::<u32>().counters().thread_cache(4).build();
+                    assert_eq!(q.len(), Some(0));
+                    for i in 0..5 {
+                        q.push(i);
+                    }
+                    q.pop();
+                    assert_eq!(q.len(), Some(4));
+                    while q.pop().is_some() {}
+                    assert_eq!(q.len(), Some(0));
+                }
+
+                /// 12. pop_timeout на пустой очереди возвращает None не раньше таймаута
+                #[test]
+                fn test_pop_timeout_expires() {
+                    let q: MSQueue<u32> = MSQueue::default();
+                    let start = Instant::now();
+                    assert_eq!(q.pop_timeout(Duration::from_millis(20)), None);
+                    assert!(start.elapsed() >= Duration::from_millis(20));
+                    assert!(!q.waiters.has_waiters());
+
+                    q.push(5);
+                    assert_eq!(q.pop_timeout(Duration::ZERO), Some(5));
+                }
+
+                /// 13. Запаркованные потребители просыпаются от push
+                #[test]
+                fn test_pop_blocking_wakes_on_push() {
+                    let q: Arc<MSQueue<u32>> = Arc::new(MSQueue::default());
+                    let consumers: Vec<_> = (0..3)
+                        .map(|_| {
+                            let qc = Arc::clone(&q);
+                            thread::spawn(move || qc.pop_blocking())
+                        })
+                        .collect();
+
+                    // Ждём, пока все запаркуются, — иначе проверка ничего не докажет
+                    while q.waiters.len() < 3 {
+                        thread::yield_now();
+                    }
+                    for i in 0..3 {
+                        q.push(i);
+                    }
+                    let mut got: Vec<_> =
+                        consumers.into_iter().map(|c| c.join().unwrap()).collect();
+                    got.sort();
+                    assert_eq!(got, vec![0, 1, 2]);
+                    assert!(!q.waiters.has_waiters());
+                }
+
+                /// 14. pop_async: задача ждёт push, отменённый future не теряет пробуждение
+                #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+                async fn test_pop_async() {
+                    let q: Arc<MSQueue<u32>> = Arc::new(MSQueue::default());
+
+                    q.push(1);
+                    assert_eq!(q.pop_async().await, 1);
+
+                    let qc = Arc::clone(&q);
+                    let consumer = tokio::spawn(async move { qc.pop_async().await });
+                    // Отменённый future: зарегистрировался и исчез
+                    let cancelled =
+                        tokio::time::timeout(Duration::from_millis(10), q.pop_async()).await;
+                    assert!(cancelled.is_err());
+
+                    q.push(2);
+                    assert_eq!(consumer.await.unwrap(), 2);
+                    assert!(!q.waiters.has_waiters());
+                }
+            }
+        };
     }
 
-    /// 5. Проверяем mix: 4 потока, в каждом чередуется push/pop
-    #[test]
-    fn test_mix_push_pop() {
-        let q = Arc::new(MSQueue::new());
-        let threads = 4;
-        let iters = 500;
-        let mut handles = vec![];
-
-        // Каждый поток: в цикле (push + pop), многократно
-        for t in 0..threads {
-            let qc = Arc::clone(&q);
-            handles.push(thread::spawn(move || {
-                let start = t * 1000;
-                for i in 0..iters {
-                    qc.push(start + i);
-                    let _ = qc.pop();
+    /// Тесты peek_with — только для эпохальных схем: у [`PeekableQueue`]
+    /// `peek_with` есть лишь при `R: EpochReclaimer`.
+    macro_rules! ms_queue_peek_tests {
+        (mod $name:ident, reclaimer: $R:ty) => {
+            mod $name {
+                use super::*;
+                use std::sync::Arc;
+                use std::thread;
+
+                fn builder<T>() -> MSQueueBuilder<T, $R> {
+                    MSQueueBuilder::default()
                 }
-            }));
-        }
 
-        for h in handles {
-            h.join().unwrap();
-        }
+                /// 1. is_empty и peek_with не меняют очередь
+                #[test]
+                fn test_is_empty_and_peek() {
+                    let q = builder::<u32>().peekable();
+                    assert!(q.is_empty());
+                    assert_eq!(q.peek_with(|v| *v), None);
+
+                    q.push(1);
+                    q.push(2);
+                    assert!(!q.is_empty());
+                    assert_eq!(q.peek_with(|v| v + 10), Some(11));
+                    assert_eq!(q.peek_with(|v| *v), Some(1));
+
+                    assert_eq!(q.pop(), Some(1));
+                    assert_eq!(q.peek_with(|v| *v), Some(2));
+                    assert_eq!(q.pop(), Some(2));
+                    assert!(q.is_empty());
+                }
+
+                /// 2. peek_with и len под конкурентными push/pop
+                // peek_with видит только вставленные значения, а после остановки
+                // счётчики сходятся с реальным содержимым.
+                #[test]
+                fn test_concurrent_peek_and_len() {
+                    let q = Arc::new(builder::<usize>().counters().peekable());
+                    let per_thread = 1000;
 
-        // Сколько осталось — не знаем, но проверяем, что программа не падает
-        let mut values = Vec::new();
+                    let writers: Vec<_> = (0..2)
+                        .map(|t| {
+                            let qc = Arc::clone(&q);
+                            thread::spawn(move || {
+                                for i in 0..per_thread {
+                                    qc.push(t * per_thread + i);
+                                    if i % 2 == 0 {
+                                        qc.pop();
+                                    }
+                                }
+                            })
+                        })
+                        .collect();
+                    let reader = {
+                        let qc = Arc::clone(&q);
+                        thread::spawn(move || {
+                            for _ in 0..per_thread {
+                                if let Some(v) = qc.peek_with(|v| *v) {
+                                    assert!(v < 2 * per_thread);
+                                }
+                                assert!(qc.len().unwrap() <= 2 * per_thread);
+                            }
+                        })
+                    };
+                    for w in writers {
+                        w.join().unwrap();
+                    }
+                    reader.join().unwrap();
+
+                    let mut left = 0;
+                    while q.pop().is_some() {
+                        left += 1;
+                    }
+                    assert_eq!(left, per_thread);
+                    assert_eq!(q.len(), Some(0));
+                    assert!(q.is_empty());
+                }
+            }
+        };
+    }
+
+    // Общие тесты — на каждой схеме освобождения, peek_with — на эпохальных;
+    // ниже — то, что зависит от пула и от выбора схемы.
+    ms_queue_tests!(mod shared_crossbeam, reclaimer: CrossbeamEpoch, flush: || crossbeam_epoch::pin().flush());
+    ms_queue_tests!(mod shared_ebr, reclaimer: Ebr, flush: crate::ebr::flush);
+    ms_queue_tests!(mod shared_hazard, reclaimer: Hazard, flush: crate::hazard::scan);
+    ms_queue_peek_tests!(mod peek_crossbeam, reclaimer: CrossbeamEpoch);
+    ms_queue_peek_tests!(mod peek_ebr, reclaimer: Ebr);
+
+    /// Значения, вставленные и снятые несколькими потоками, доставлены ровно один раз.
+    fn all_values_delivered<R: Reclaimer>(q: MSQueue<usize, R>) {
+        let q = Arc::new(q);
+        let threads = 4;
+        let per_thread = 1000;
+
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let qc = Arc::clone(&q);
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for i in 0..per_thread {
+                        qc.push(t * per_thread + i);
+                        popped.extend(qc.pop());
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut values: Vec<_> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
         while let Some(x) = q.pop() {
             values.push(x);
         }
-        // Можно сделать какие-то asserts, но результат не гарантирован
-        // (так как concurrent).
+        values.sort();
+        assert_eq!(values, (0..threads * per_thread).collect::<Vec<_>>());
     }
 
-    /// 6. Stress-тест: много push/pop подряд разными потоками
+    /// 1. Пул узлов: значения доставлены ровно один раз при каждой схеме
     #[test]
-    fn test_stress() {
-        let q = Arc::new(MSQueue::new());
-
-        let thread_count = 8;
-        let per_thread = 2000;
-        let mut handles = vec![];
-
-        for _ in 0..thread_count {
-            let qc = Arc::clone(&q);
-            handles.push(thread::spawn(move || {
-                for i in 0..per_thread {
-                    qc.push(i);
-                    // Сразу пытаемся pop()
-                    let _ = qc.pop();
-                }
-            }));
+    fn test_pooled_all_values_delivered() {
+        let builder = MSQueue::<usize>::builder().thread_cache(16).max_pooled(64);
+        all_values_delivered(builder.build());
+        all_values_delivered(builder.reclaimer::<Ebr>().build());
+        all_values_delivered(builder.reclaimer::<Hazard>().build());
+    }
+
+    /// 2. Узлы, отцеплённые pop, возвращаются в пул и идут под новые push
+    #[test]
+    fn test_pool_reuses_nodes() {
+        let q = MSQueue::<u64>::builder()
+            .reclaimer::<Hazard>()
+            .thread_cache(8)
+            .build();
+        for i in 0..4 {
+            q.push(i);
         }
+        while q.pop().is_some() {}
+        // Узлы никто не защищает — скан возвращает их в пул
+        crate::hazard::scan();
+        // Вернулись dummy и три узла; четвёртый теперь dummy
+        assert_eq!(q.pooled(), 4);
 
-        for h in handles {
-            h.join().unwrap();
+        q.push(10);
+        assert_eq!(q.pooled(), 3);
+        assert_eq!(q.pop(), Some(10));
+    }
+
+    /// 3. Каждый отцеплённый узел (включая исходный dummy) отдаётся схеме один раз
+    // С пулом все они возвращаются туда после скана.
+    #[test]
+    fn test_each_retired_node_recycled_once() {
+        let q = MSQueue::<u64>::builder()
+            .reclaimer::<Hazard>()
+            .thread_cache(64)
+            .build();
+        for i in 0..20 {
+            q.push(i);
+        }
+        for _ in 0..7 {
+            q.pop();
         }
+        crate::hazard::scan();
+        assert_eq!(q.pooled(), 7);
+
+        while q.pop().is_some() {}
+        crate::hazard::scan();
+        assert_eq!(q.pooled(), 20);
+        // Последний узел — текущий dummy — освобождает Drop
+    }
 
-        let mut leftover = 0;
-        while let Some(_val) = q.pop() {
-            leftover += 1;
+    /// 4. Значение, на которое смотрит peek_with, переживает конкурентный pop
+    // pop отдаёт клон, а строка в узле дропается только после guard'а.
+    #[test]
+    fn test_peek_survives_pop_of_non_copy_value() {
+        let q = MSQueue::<String>::builder().reclaimer::<Ebr>().peekable();
+        q.push("first".to_string());
+        q.push("second".to_string());
+
+        q.peek_with(|first| {
+            let popped = thread::scope(|s| s.spawn(|| (q.pop(), q.pop())).join().unwrap());
+            assert_eq!(popped.0.as_deref(), Some("first"));
+            assert_eq!(popped.1.as_deref(), Some("second"));
+            drop(popped);
+            crate::ebr::flush();
+            assert_eq!(first, "first");
+        });
+        assert!(q.is_empty());
+    }
+
+    /// 5. Очередь с peek: каждое значение дропается ровно один раз
+    // Вместе со своим узлом или в Drop очереди; исходный dummy пуст.
+    fn peekable_drops_each_value_once<R: Reclaimer>(
+        builder: MSQueueBuilder<Arc<()>, R>,
+        flush: impl Fn(),
+    ) {
+        let value = Arc::new(());
+        let q = builder.peekable();
+        for _ in 0..5 {
+            q.push(Arc::clone(&value));
         }
-        println!("Осталось {} элементов после stress-теста.", leftover);
+        for _ in 0..3 {
+            drop(q.pop());
+        }
+        drop(q);
+        flush();
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_peekable_drops_each_value_once() {
+        let builder = MSQueue::<Arc<()>, Ebr>::builder();
+        peekable_drops_each_value_once(builder, crate::ebr::flush);
+        peekable_drops_each_value_once(builder.thread_cache(4), crate::ebr::flush);
+        let builder = builder.reclaimer::<Hazard>();
+        peekable_drops_each_value_once(builder, crate::hazard::scan);
+        peekable_drops_each_value_once(builder.thread_cache(4), crate::hazard::scan);
     }
 }
@@ -0,0 +1,365 @@
+//! Пул узлов для lock-free структур.
+//!
+//! Каждый push в `TreiberStack`/`MSQueue` выделяет узел, а каждый pop
+//! отдаёт старый узел схеме освобождения памяти. С пулом узел, который
+//! схема признала безопасным, не возвращается аллокатору, а попадает
+//! в кэш потока, где его подберёт следующий push.
+//!
+//! Устройство:
+//! 1) кэш потока — `thread_local!`-список кэшей по одному на пул; push/pop
+//!    берут и кладут узлы в него без блокировок и атомарных RMW на пул;
+//! 2) общий пул — переполнение кэшей под мьютексом; кэш отдаёт туда
+//!    половину, когда вырастает больше `thread_cache`, забирает пачку,
+//!    когда пуст, и сдаёт всё при завершении потока;
+//! 3) сверх `max_pooled` узлов в общем пуле память возвращается аллокатору.
+//!
+//! Узел возвращается в пул только через `Reclaimer::retire_with`, то есть
+//! когда схема гарантирует, что на него больше никто не смотрит.
+
+use std::{
+    alloc::{self, Layout},
+    cell::RefCell,
+    mem,
+    ptr::{self, NonNull},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Weak,
+    },
+};
+
+use parking_lot::Mutex;
+
+/// Настройки пула (см. билдеры `TreiberStack::builder`, `MSQueue::builder`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Сколько свободных узлов держит кэш одного потока.
+    pub thread_cache: usize,
+    /// Сколько узлов может лежать в общем пуле; остальное освобождается.
+    pub max_pooled: usize,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            thread_cache: 64,
+            max_pooled: 4096,
+        }
+    }
+}
+
+/// Свободный блок памяти под один узел.
+struct Block(NonNull<u8>);
+
+// Блок — просто память без значения.
+unsafe impl Send for Block {}
+
+/// Кэш одного пула в одном потоке.
+///
+/// Пул держится по `Weak`: кэш не продлевает ему жизнь, а `Layout` нужен,
+/// чтобы освободить блоки, если пул умер раньше потока.
+struct LocalCache {
+    pool: Weak<NodePool>,
+    layout: Layout,
+    blocks: Vec<Block>,
+}
+
+impl Drop for LocalCache {
+    /// Поток завершается или пул умер: блоки — в общий пул или аллокатору.
+    fn drop(&mut self) {
+        let blocks = mem::take(&mut self.blocks);
+        match self.pool.upgrade() {
+            Some(pool) => pool.spill(blocks),
+            None => {
+                for block in blocks {
+                    unsafe { alloc::dealloc(block.0.as_ptr(), self.layout) };
+                }
+            }
+        }
+    }
+}
+
+thread_local! {
+    static CACHES: RefCell<Vec<LocalCache>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Пул блоков под узлы одного размещения (`Layout`).
+///
+/// Пул нетипизирован: отложенное возвращение узла захватывает только
+/// `Arc<NodePool>`, поэтому не требует `T: 'static` от значений.
+/// Блоки выделяются глобальным аллокатором с `Layout::new::<N>()`, так что
+/// узел из пула можно освободить как `Box<N>`, а `Box<N>` — вернуть в пул.
+pub(crate) struct NodePool {
+    layout: Layout,
+    overflow: Mutex<Vec<Block>>,
+    /// Блоков в кэшах потоков и общем пуле вместе.
+    pooled: AtomicUsize,
+    config: PoolConfig,
+}
+
+impl NodePool {
+    /// Пул под узлы типа `N`.
+    pub(crate) fn new<N>(config: PoolConfig) -> Self {
+        let layout = Layout::new::<N>();
+        assert!(layout.size() > 0, "zero-sized nodes are not pooled");
+        NodePool {
+            layout,
+            overflow: Mutex::new(Vec::new()),
+            pooled: AtomicUsize::new(0),
+            config: PoolConfig {
+                thread_cache: config.thread_cache.max(1),
+                ..config
+            },
+        }
+    }
+
+    /// Вызывает `f` с кэшем этого пула в текущем потоке.
+    ///
+    /// `None`, если кэша нет: поток уже завершается или кэш занят выше
+    /// по стеку (например, drop значения сам вернул узел в пул).
+    fn with_cache<U>(self: &Arc<Self>, f: impl FnOnce(&mut Vec<Block>) -> U) -> Option<U> {
+        CACHES
+            .try_with(|caches| {
+                let mut caches = caches.try_borrow_mut().ok()?;
+                let me = Arc::as_ptr(self);
+                let i = match caches.iter().position(|c| ptr::eq(c.pool.as_ptr(), me)) {
+                    Some(i) => i,
+                    None => {
+                        // Заодно выбрасываем кэши умерших пулов
+                        caches.retain(|c| c.pool.strong_count() > 0);
+                        caches.push(LocalCache {
+                            pool: Arc::downgrade(self),
+                            layout: self.layout,
+                            blocks: Vec::with_capacity(self.config.thread_cache),
+                        });
+                        caches.len() - 1
+                    }
+                };
+                Some(f(&mut caches[i].blocks))
+            })
+            .ok()
+            .flatten()
+    }
+
+    /// Размещает `node` в блоке из пула (или в новой памяти).
+    /// Результат возвращается в пул через [`recycle`](Self::recycle).
+    pub(crate) fn alloc<N>(self: &Arc<Self>, node: N) -> *mut N {
+        debug_assert_eq!(Layout::new::<N>(), self.layout);
+        let ptr = match self.take() {
+            Some(block) => block.0.as_ptr(),
+            None => {
+                let ptr = unsafe { alloc::alloc(self.layout) };
+                if ptr.is_null() {
+                    alloc::handle_alloc_error(self.layout);
+                }
+                ptr
+            }
+        } as *mut N;
+        unsafe { ptr.write(node) };
+        ptr
+    }
+
+    /// Дропает узел и возвращает его память в пул.
+    ///
+    /// # Safety
+    ///
+    /// `node` получен из [`alloc`](Self::alloc) этого пула, больше никем
+    /// не используется и содержит живое значение `N`.
+    pub(crate) unsafe fn recycle<N>(self: &Arc<Self>, node: *mut N) {
+        ptr::drop_in_place(node);
+        let mut block = Some(Block(NonNull::new_unchecked(node as *mut u8)));
+        self.pooled.fetch_add(1, Ordering::Relaxed);
+
+        let spill = self.with_cache(|cache| {
+            cache.extend(block.take());
+            // Кэш переполнен — половину в общий пул
+            (cache.len() > self.config.thread_cache).then(|| {
+                let half = cache.len() / 2;
+                cache.drain(..half).collect()
+            })
+        });
+        match (spill, block) {
+            (Some(Some(spill)), _) => self.spill(spill),
+            // Кэша нет — сразу в общий пул
+            (_, Some(block)) => self.spill(vec![block]),
+            _ => {}
+        }
+    }
+
+    /// Свободный блок из кэша потока, а если он пуст — пачка из общего пула.
+    fn take(self: &Arc<Self>) -> Option<Block> {
+        let block = self
+            .with_cache(|cache| {
+                if cache.is_empty() {
+                    let mut overflow = self.overflow.lock();
+                    let batch = overflow.len().min(self.config.thread_cache / 2 + 1);
+                    let start = overflow.len() - batch;
+                    cache.extend(overflow.drain(start..));
+                }
+                cache.pop()
+            })
+            .unwrap_or_else(|| self.overflow.lock().pop());
+        if block.is_some() {
+            self.pooled.fetch_sub(1, Ordering::Relaxed);
+        }
+        block
+    }
+
+    /// Кладёт блоки в общий пул; не поместившиеся — обратно аллокатору.
+    fn spill(&self, blocks: Vec<Block>) {
+        let mut overflow = self.overflow.lock();
+        let room = self.config.max_pooled.saturating_sub(overflow.len());
+        let mut blocks = blocks.into_iter();
+        overflow.extend(blocks.by_ref().take(room));
+        drop(overflow);
+        for block in blocks {
+            self.pooled.fetch_sub(1, Ordering::Relaxed);
+            self.dealloc(block);
+        }
+    }
+
+    /// Сколько свободных узлов в пуле (кэши всех потоков + общий пул).
+    pub(crate) fn pooled(&self) -> usize {
+        self.pooled.load(Ordering::Relaxed)
+    }
+
+    fn dealloc(&self, block: Block) {
+        unsafe { alloc::dealloc(block.0.as_ptr(), self.layout) };
+    }
+}
+
+impl Drop for NodePool {
+    /// Освобождает общий пул и кэш текущего потока; кэши других потоков
+    /// освободят свои блоки сами — при завершении или при следующем
+    /// обращении к любому пулу.
+    fn drop(&mut self) {
+        for block in mem::take(self.overflow.get_mut()) {
+            self.dealloc(block);
+        }
+        let _ = CACHES.try_with(|caches| {
+            if let Ok(mut caches) = caches.try_borrow_mut() {
+                caches.retain(|c| c.pool.strong_count() > 0);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    fn pool(thread_cache: usize, max_pooled: usize) -> Arc<NodePool> {
+        Arc::new(NodePool::new::<u64>(PoolConfig {
+            thread_cache,
+            max_pooled,
+        }))
+    }
+
+    #[test]
+    fn test_recycled_node_is_reused() {
+        let pool = pool(4, 16);
+        let a = pool.alloc(1u64);
+        unsafe { pool.recycle(a) };
+        assert_eq!(pool.pooled(), 1);
+
+        let b = pool.alloc(2u64);
+        assert_eq!(a, b, "узел взят из кэша");
+        assert_eq!(unsafe { *b }, 2);
+        unsafe { pool.recycle(b) };
+    }
+
+    #[test]
+    fn test_overflow_is_bounded() {
+        let pool = pool(4, 2);
+        let nodes: Vec<_> = (0..20u64).map(|i| pool.alloc(i)).collect();
+        for n in nodes {
+            unsafe { pool.recycle(n) };
+        }
+        // Кэш потока не больше thread_cache, общий пул не больше max_pooled
+        assert!(pool.pooled() <= 4 + 2);
+    }
+
+    #[test]
+    fn test_nodes_move_between_threads() {
+        let pool = pool(2, 64);
+
+        // Один поток возвращает много узлов — излишек уходит в общий пул
+        let p = Arc::clone(&pool);
+        thread::spawn(move || {
+            let nodes: Vec<_> = (0..10u64).map(|i| p.alloc(i)).collect();
+            for n in nodes {
+                unsafe { p.recycle(n) };
+            }
+        })
+        .join()
+        .unwrap();
+        let pooled = pool.pooled();
+        assert!(pooled > 0);
+
+        // Другой поток берёт их оттуда
+        let p = Arc::clone(&pool);
+        thread::spawn(move || {
+            let n = p.alloc(7u64);
+            unsafe { p.recycle(n) };
+        })
+        .join()
+        .unwrap();
+        assert_eq!(pool.pooled(), pooled);
+    }
+
+    /// Кэш потока виден только ему: пока владелец жив, другой поток
+    /// его узел не получит, а после завершения узел уходит в общий пул.
+    #[test]
+    fn test_thread_cache_is_private() {
+        let pool = pool(4, 16);
+        let (node_tx, node_rx) = mpsc::channel();
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+
+        let p = Arc::clone(&pool);
+        let owner = thread::spawn(move || {
+            let n = p.alloc(1u64);
+            unsafe { p.recycle(n) };
+            node_tx.send(n as usize).unwrap();
+            done_rx.recv().unwrap();
+        });
+        let cached = node_rx.recv().unwrap() as *mut u64;
+        assert_eq!(pool.pooled(), 1);
+
+        let n = pool.alloc(2u64);
+        assert_ne!(n, cached, "чужой кэш не трогаем");
+        unsafe { pool.recycle(n) };
+
+        done_tx.send(()).unwrap();
+        owner.join().unwrap();
+        assert_eq!(pool.pooled(), 2);
+        assert_eq!(pool.overflow.lock().len(), 1);
+    }
+
+    /// Пул умер раньше потока: блоки из кэша потока освобождаются им самим.
+    #[test]
+    fn test_pool_dropped_before_thread() {
+        let pool = pool(4, 16);
+        let (tx, rx) = mpsc::channel::<()>();
+        let p = Arc::clone(&pool);
+        let owner = thread::spawn(move || {
+            let n = p.alloc(1u64);
+            unsafe { p.recycle(n) };
+            drop(p);
+            rx.recv().unwrap();
+        });
+        drop(pool);
+        tx.send(()).unwrap();
+        owner.join().unwrap();
+    }
+
+    #[test]
+    fn test_values_are_dropped_on_recycle() {
+        let value = Arc::new(());
+        let pool = Arc::new(NodePool::new::<Arc<()>>(PoolConfig::default()));
+        let n = pool.alloc(Arc::clone(&value));
+        assert_eq!(Arc::strong_count(&value), 2);
+        unsafe { pool.recycle(n) };
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,573 @@
+//! Quiescent-State-Based Reclamation (QSBR).
+//!
+//! Вариант освобождения памяти для потоков, у которых естественно есть
+//! "точки покоя" (например, конец итерации event loop): поток периодически
+//! сообщает [`LocalHandle::quiescent`], что не держит ни одной ссылки на
+//! разделяемые объекты. Чтения между такими точками ничего не стоят —
+//! ни `pin()`, ни барьеров.
+//!
+//! Объект, отложенный через [`LocalHandle::retire`], освобождается, когда
+//! каждый участник в сети (online) прошёл точку покоя после retire.
+//! Поток, который надолго блокируется (ввод-вывод, сон), должен уйти
+//! в [`LocalHandle::offline`], иначе он задерживает освобождение для всех.
+//!
+//! Реестр участников общий с [`crate::ebr`]: [`Collector`] с фиксированным
+//! числом слотов ([`MAX_THREADS`]), [`LocalHandle`] на поток, мусор
+//! отключившихся участников переходит к коллектору. Свободные функции
+//! работают с коллектором по умолчанию; поток, впервые вызвавший любую
+//! из них, регистрируется в сети.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{self, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::ebr::deferred::Deferred;
+use crate::registry::{self, Registry, ThreadHandle};
+
+pub use crate::registry::MAX_THREADS;
+
+/// Флаг "в сети" в младшем бите состояния участника.
+const ONLINE: usize = 1;
+
+/// `a` не раньше `b` (с учётом переполнения счётчика эпох).
+fn reached(a: usize, b: usize) -> bool {
+    a.wrapping_sub(b) as isize >= 0
+}
+
+/// Разделяемое состояние коллектора:
+/// 1) epoch — глобальный счётчик, растёт при каждом запечатывании пакета.
+///    Шаг — 2: младший бит всегда свободен под флаг `ONLINE` в состоянии участника.
+/// 2) threads — реестр участников.
+/// 3) orphans — пакеты отключившихся участников.
+struct Global {
+    epoch: AtomicUsize,
+    threads: Registry<Participant>,
+    orphans: Mutex<Vec<Batch>>,
+}
+
+impl Drop for Global {
+    fn drop(&mut self) {
+        // Участников не осталось (каждый держит клон Collector) — всё можно освободить.
+        for batch in self.orphans.get_mut().unwrap().drain(..) {
+            batch.run();
+        }
+    }
+}
+
+/// Состояние участника в слоте реестра: эпоха последней точки покоя
+/// и флаг `ONLINE` в младшем бите.
+struct Participant {
+    state: AtomicUsize,
+}
+
+/// Пакет отложенных функций, запечатанный в эпохе `epoch`.
+/// Его можно выполнить, когда все участники в сети прошли эту эпоху.
+struct Batch {
+    epoch: usize,
+    items: Vec<Deferred>,
+}
+
+// Пакет переезжает в `orphans` и выполняется другим потоком.
+// Сами объекты к этому моменту уже недостижимы из структуры данных.
+unsafe impl Send for Batch {}
+
+impl Batch {
+    fn run(self) {
+        for d in self.items {
+            d.call();
+        }
+    }
+}
+
+/// Коллектор QSBR: глобальная эпоха и реестр участников.
+/// Клонирование дешёвое: клоны ссылаются на один и тот же коллектор.
+#[derive(Clone)]
+pub struct Collector {
+    global: Arc<Global>,
+}
+
+impl Default for Collector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Collector {
+    pub fn new() -> Self {
+        Collector {
+            global: Arc::new(Global {
+                epoch: AtomicUsize::new(0),
+                threads: Registry::new(|| Participant {
+                    state: AtomicUsize::new(0),
+                }),
+                orphans: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Регистрирует нового участника. Он сразу в сети
+    /// и считается прошедшим точку покоя.
+    ///
+    /// # Panics
+    ///
+    /// Если все `MAX_THREADS` слотов заняты.
+    pub fn register(&self) -> LocalHandle {
+        let handle = LocalHandle {
+            index: self.global.threads.acquire("qsbr collector"),
+            collector: self.clone(),
+            current: RefCell::new(Vec::new()),
+            sealed: RefCell::new(VecDeque::new()),
+            online: Cell::new(false),
+            _marker: PhantomData,
+        };
+        handle.online();
+        handle
+    }
+
+    /// Текущая глобальная эпоха.
+    pub fn epoch(&self) -> usize {
+        self.global.epoch.load(Ordering::Relaxed) >> 1
+    }
+
+    /// Самая старая точка покоя среди участников в сети, включая `own`.
+    /// Всё, что запечатано не позже неё, можно освобождать.
+    fn safe_epoch(&self, own: usize) -> usize {
+        let mut min = own;
+        for (_, thr) in self.global.threads.active() {
+            let state = thr.state.load(Ordering::SeqCst);
+            if state & ONLINE != 0 && !reached(state & !ONLINE, min) {
+                min = state & !ONLINE;
+            }
+        }
+        min
+    }
+
+    /// Забирает пакеты сирот, которые уже можно выполнить.
+    fn take_ready_orphans(&self, safe: usize) -> Vec<Batch> {
+        let mut orphans = self.global.orphans.lock().unwrap();
+        let (ready, rest) = orphans.drain(..).partition(|b| reached(safe, b.epoch));
+        *orphans = rest;
+        ready
+    }
+}
+
+impl PartialEq for Collector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.global, &other.global)
+    }
+}
+
+impl Eq for Collector {}
+
+impl fmt::Debug for Collector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Collector")
+            .field("epoch", &self.epoch())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Дескриптор участника. Принадлежит одному потоку (`!Send`).
+///
+/// Пока участник в сети, указатели, прочитанные им после последней точки
+/// покоя, остаются действительными до следующей точки покоя (или offline).
+pub struct LocalHandle {
+    index: usize,
+    collector: Collector,
+    /// Отложенное после последней точки покоя (ещё не запечатано).
+    current: RefCell<Vec<Deferred>>,
+    /// Запечатанные пакеты в порядке эпох.
+    sealed: RefCell<VecDeque<Batch>>,
+    online: Cell<bool>,
+    _marker: PhantomData<*const ()>, // !Send + !Sync
+}
+
+impl LocalHandle {
+    fn participant(&self) -> &Participant {
+        self.collector.global.threads.get(self.index)
+    }
+
+    /// Точка покоя: поток не держит ссылок на разделяемые объекты.
+    ///
+    /// Запечатывает отложенное с прошлой точки покоя, объявляет текущую
+    /// эпоху и выполняет пакеты, которые все участники в сети уже прошли.
+    /// Отложенные функции выполняются в этом же потоке.
+    pub fn quiescent(&self) {
+        if !self.online.get() {
+            return;
+        }
+        self.seal();
+        let epoch = self.collector.global.epoch.load(Ordering::SeqCst);
+        self.participant()
+            .state
+            .store(epoch | ONLINE, Ordering::SeqCst);
+        self.reclaim(epoch);
+    }
+
+    /// Уходит из сети: участник больше не задерживает освобождение
+    /// и не должен обращаться к разделяемым объектам до [`online`](Self::online).
+    pub fn offline(&self) {
+        if !self.online.replace(false) {
+            return;
+        }
+        self.seal();
+        let epoch = self.collector.global.epoch.load(Ordering::SeqCst);
+        self.participant().state.store(epoch, Ordering::SeqCst);
+        self.reclaim(epoch);
+    }
+
+    /// Возвращается в сеть (это тоже точка покоя).
+    pub fn online(&self) {
+        if self.online.replace(true) {
+            return;
+        }
+        let epoch = self.collector.global.epoch.load(Ordering::SeqCst);
+        self.participant()
+            .state
+            .store(epoch | ONLINE, Ordering::SeqCst);
+        // Барьер в паре с чтением state в safe_epoch(): либо освобождающий
+        // увидит нас в сети, либо мы увидим уже отцеплённые объекты.
+        atomic::fence(Ordering::SeqCst);
+    }
+
+    /// В сети ли участник.
+    pub fn is_online(&self) -> bool {
+        self.online.get()
+    }
+
+    /// Откладывает функцию до момента, когда все участники в сети
+    /// пройдут точку покоя.
+    pub fn defer<F: FnOnce() + Send + 'static>(&self, f: F) {
+        unsafe { self.defer_unchecked(f) }
+    }
+
+    /// Как [`defer`](Self::defer), но без ограничений на `F`.
+    ///
+    /// # Safety
+    ///
+    /// `f` может быть вызвана в другом потоке (если участник отключится
+    /// раньше) и после того, как истекут заимствования внутри неё.
+    pub unsafe fn defer_unchecked<F: FnOnce()>(&self, f: F) {
+        self.current.borrow_mut().push(Deferred::new(f));
+    }
+
+    /// Откладывает освобождение `ptr` (как `Box<T>`).
+    ///
+    /// # Safety
+    ///
+    /// `ptr` получен из `Box::into_raw`, уже отцеплен от структуры данных
+    /// и не будет отложен повторно. `T` может быть освобождён в другом потоке.
+    pub unsafe fn retire<T>(&self, ptr: *mut T) {
+        self.defer_unchecked(move || drop(Box::from_raw(ptr)));
+    }
+
+    /// Сколько отложенных функций ждёт выполнения у участника.
+    pub fn pending(&self) -> usize {
+        self.current.borrow().len()
+            + self
+                .sealed
+                .borrow()
+                .iter()
+                .map(|b| b.items.len())
+                .sum::<usize>()
+    }
+
+    /// Номер слота участника в реестре коллектора.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Коллектор, в котором зарегистрирован участник.
+    pub fn collector(&self) -> &Collector {
+        &self.collector
+    }
+
+    /// Запечатывает текущий пакет новой эпохой. Объявления точек покоя,
+    /// сделанные после этого, увидят эпоху не меньше пакетной.
+    fn seal(&self) {
+        let items = std::mem::take(&mut *self.current.borrow_mut());
+        if items.is_empty() {
+            return;
+        }
+        let epoch = self
+            .collector
+            .global
+            .epoch
+            .fetch_add(2, Ordering::SeqCst)
+            .wrapping_add(2);
+        self.sealed.borrow_mut().push_back(Batch { epoch, items });
+    }
+
+    /// Выполняет готовые пакеты: свои и осиротевшие.
+    fn reclaim(&self, own: usize) {
+        let has_sealed = !self.sealed.borrow().is_empty();
+        let has_orphans = !self.collector.global.orphans.lock().unwrap().is_empty();
+        if !has_sealed && !has_orphans {
+            return;
+        }
+
+        let safe = self.collector.safe_epoch(own);
+        let mut ready = Vec::new();
+        {
+            let mut sealed = self.sealed.borrow_mut();
+            while sealed.front().is_some_and(|b| reached(safe, b.epoch)) {
+                ready.push(sealed.pop_front().unwrap());
+            }
+        }
+        if has_orphans {
+            ready.extend(self.collector.take_ready_orphans(safe));
+        }
+
+        // Вне заимствований: отложенная функция может снова вызвать retire().
+        for batch in ready {
+            batch.run();
+        }
+    }
+}
+
+impl Drop for LocalHandle {
+    fn drop(&mut self) {
+        self.offline();
+
+        // Что не освободилось — передаём коллектору.
+        let sealed: Vec<_> = self.sealed.get_mut().drain(..).collect();
+        if !sealed.is_empty() {
+            self.collector.global.orphans.lock().unwrap().extend(sealed);
+        }
+
+        self.collector.global.threads.release(self.index);
+    }
+}
+
+/// Коллектор по умолчанию с ленивой (Lazy) инициализацией.
+static DEFAULT_COLLECTOR: once_cell::sync::Lazy<Collector> =
+    once_cell::sync::Lazy::new(Collector::new);
+
+// thread_local! хранит Option<LocalHandle> для каждого потока.
+// Если None, значит поток ещё не зарегистрирован в коллекторе по умолчанию.
+thread_local! {
+    static HANDLE: ThreadHandle<LocalHandle> = const { RefCell::new(None) };
+}
+
+/// Коллектор, которым пользуются свободные функции модуля.
+pub fn default_collector() -> &'static Collector {
+    &DEFAULT_COLLECTOR
+}
+
+/// Дескриптор текущего потока в коллекторе по умолчанию.
+fn with_handle<R>(f: impl Fn(&LocalHandle) -> R) -> R {
+    registry::with_thread_handle(&HANDLE, || DEFAULT_COLLECTOR.register(), f)
+}
+
+/// Точка покоя текущего потока (см. [`LocalHandle::quiescent`]).
+pub fn quiescent() {
+    with_handle(LocalHandle::quiescent);
+}
+
+/// Уводит текущий поток из сети (см. [`LocalHandle::offline`]).
+pub fn offline() {
+    with_handle(LocalHandle::offline);
+}
+
+/// Возвращает текущий поток в сеть (см. [`LocalHandle::online`]).
+pub fn online() {
+    with_handle(LocalHandle::online);
+}
+
+/// Откладывает освобождение `ptr` в коллекторе по умолчанию.
+///
+/// # Safety
+///
+/// См. [`LocalHandle::retire`].
+pub unsafe fn retire<T>(ptr: *mut T) {
+    with_handle(|h| h.retire(ptr));
+}
+
+/// Отключает текущий поток от коллектора по умолчанию. Его мусор
+/// переходит к коллектору и будет освобождён другими участниками.
+pub fn unregister_thread() {
+    registry::unregister_thread_handle(&HANDLE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn counter(freed: &Arc<AtomicUsize>) -> impl FnOnce() + Send + 'static {
+        let freed = freed.clone();
+        move || {
+            freed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_single_participant() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let handle = collector.register();
+
+        handle.defer(counter(&freed));
+        assert_eq!(handle.pending(), 1);
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+
+        // Единственный участник прошёл точку покоя — можно освобождать
+        handle.quiescent();
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+        assert_eq!(handle.pending(), 0);
+    }
+
+    #[test]
+    fn test_reader_blocks_until_quiescent() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let reader = collector.register();
+        let writer = collector.register();
+
+        writer.defer(counter(&freed));
+        writer.quiescent();
+        writer.quiescent();
+        assert_eq!(freed.load(Ordering::Relaxed), 0, "читатель ещё не в покое");
+
+        reader.quiescent();
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+        writer.quiescent();
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_offline_does_not_block() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let sleeper = collector.register();
+        let writer = collector.register();
+
+        sleeper.offline();
+        assert!(!sleeper.is_online());
+        writer.defer(counter(&freed));
+        writer.quiescent();
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+
+        // Вернувшийся в сеть участник снова учитывается
+        sleeper.online();
+        writer.defer(counter(&freed));
+        writer.quiescent();
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+        sleeper.quiescent();
+        writer.quiescent();
+        assert_eq!(freed.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_orphans_are_reclaimed() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let reader = collector.register();
+
+        let (c, f) = (collector.clone(), freed.clone());
+        thread::spawn(move || {
+            let handle = c.register();
+            handle.defer(counter(&f));
+            handle.quiescent();
+        })
+        .join()
+        .unwrap();
+        assert_eq!(freed.load(Ordering::Relaxed), 0);
+
+        reader.quiescent();
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_pending_freed_on_collector_drop() {
+        let freed = Arc::new(AtomicUsize::new(0));
+        let collector = Collector::new();
+        let reader = collector.register();
+        let writer = collector.register();
+
+        writer.defer(counter(&freed));
+        drop(writer);
+        drop(reader);
+        drop(collector);
+        assert_eq!(freed.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_slot_is_released() {
+        let collector = Collector::new();
+        for _ in 0..MAX_THREADS * 2 {
+            let handle = collector.register();
+            assert!(handle.index() < MAX_THREADS);
+            assert_eq!(handle.collector(), &collector);
+        }
+    }
+
+    #[test]
+    fn test_reached_wraps() {
+        assert!(reached(0, usize::MAX - 1));
+        assert!(!reached(usize::MAX - 1, 0));
+        assert!(reached(5, 5));
+    }
+
+    #[test]
+    fn test_concurrent_readers() {
+        use std::sync::atomic::AtomicPtr;
+
+        let collector = Collector::new();
+        let shared = Arc::new(AtomicPtr::new(Box::into_raw(Box::new(0usize))));
+        let threads = 4;
+        let iters = 1000;
+
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let (c, shared) = (collector.clone(), shared.clone());
+                thread::spawn(move || {
+                    let handle = c.register();
+                    for i in 0..iters {
+                        // Чтение без pin: действительно до точки покоя
+                        let p = shared.load(Ordering::Acquire);
+                        assert!(unsafe { *p } < threads * iters);
+
+                        if i % 10 == 0 {
+                            let new = Box::into_raw(Box::new(t * iters + i));
+                            let old = shared.swap(new, Ordering::AcqRel);
+                            unsafe { handle.retire(old) };
+                        }
+                        handle.quiescent();
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        drop(unsafe { Box::from_raw(shared.load(Ordering::Relaxed)) });
+    }
+
+    #[test]
+    fn test_default_collector() {
+        let value = Arc::new(());
+        thread::spawn({
+            let value = value.clone();
+            move || {
+                offline();
+                online();
+                unsafe { retire(Box::into_raw(Box::new(value))) };
+                quiescent();
+                unregister_thread();
+            }
+        })
+        .join()
+        .unwrap();
+        assert!(default_collector().epoch() > 0);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
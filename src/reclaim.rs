@@ -0,0 +1,246 @@
+//! Общий интерфейс схем освобождения памяти.
+//!
+//! [`Reclaimer`] сводит разные схемы к трём операциям — pin/protect/retire, —
+//! чтобы один и тот же алгоритм (например, [`MSQueue`](crate::ms_queue_crossbeam::MSQueue)
+//! или [`TreiberStack`](crate::treiber_stack::TreiberStack)) можно было
+//! собрать поверх любой из них и сравнить схемы на одинаковом коде.
+//!
+//! Реализации:
+//! - [`Ebr`] — эпохи из [`crate::ebr`],
+//! - [`CrossbeamEpoch`] — эпохи из `crossbeam_epoch`,
+//! - [`Hazard`] — hazard pointers из [`crate::hazard`].
+
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::{ebr, hazard};
+
+/// Сколько указателей одновременно может защищать один guard.
+/// Двух хватает и стеку (head), и очереди Майкла–Скотта (head + next).
+pub const SLOTS: usize = 2;
+
+/// Схема безопасного освобождения памяти для lock-free структур.
+///
+/// Типы-реализации — пустые маркеры; всё состояние живёт в guard'е
+/// и в самой схеме (домене/коллекторе по умолчанию).
+pub trait Reclaimer: Send + Sync + 'static {
+    /// Охрана на время одной операции над структурой.
+    type Guard;
+
+    /// Начинает операцию (вход в критическую секцию).
+    fn pin() -> Self::Guard;
+
+    /// Загружает указатель из `src` и защищает его в слоте `slot`
+    /// (`slot < SLOTS`). Указатель можно разыменовывать, пока жив guard
+    /// и слот не перезаписан.
+    fn protect<T>(guard: &mut Self::Guard, slot: usize, src: &AtomicPtr<T>) -> *mut T;
+
+    /// Откладывает освобождение `ptr` (как `Box<T>`), пока на него могут
+    /// смотреть другие потоки.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` получен из `Box::into_raw`, уже отцеплен от структуры данных
+    /// и не будет отложен повторно. `T` может быть освобождён в другом потоке.
+    unsafe fn retire<T>(guard: &Self::Guard, ptr: *mut T) {
+        Self::retire_with(guard, ptr, |p| unsafe { drop(Box::from_raw(p)) });
+    }
+
+    /// Как [`retire`](Self::retire), но когда освобождение станет безопасным,
+    /// вызывает `free(ptr)` (например, возвращает узел в пул).
+    ///
+    /// # Safety
+    ///
+    /// `ptr` уже отцеплен от структуры данных и не будет отложен повторно;
+    /// `free` корректно распоряжается им в любом потоке.
+    unsafe fn retire_with<T, F>(guard: &Self::Guard, ptr: *mut T, free: F)
+    where
+        F: FnOnce(*mut T) + Send + 'static;
+}
+
+/// EBR этого крейта ([`crate::ebr`]).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ebr;
+
+impl Reclaimer for Ebr {
+    type Guard = ebr::Guard<'static>;
+
+    fn pin() -> Self::Guard {
+        ebr::pin()
+    }
+
+    fn protect<T>(_guard: &mut Self::Guard, _slot: usize, src: &AtomicPtr<T>) -> *mut T {
+        // Закреплённого guard'а достаточно: слоты не нужны
+        src.load(Ordering::Acquire)
+    }
+
+    unsafe fn retire_with<T, F>(guard: &Self::Guard, ptr: *mut T, free: F)
+    where
+        F: FnOnce(*mut T) + Send + 'static,
+    {
+        guard.defer_unchecked(move || free(ptr));
+    }
+}
+
+/// EBR из `crossbeam_epoch` (сборщик по умолчанию).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CrossbeamEpoch;
+
+impl Reclaimer for CrossbeamEpoch {
+    type Guard = crossbeam_epoch::Guard;
+
+    fn pin() -> Self::Guard {
+        crossbeam_epoch::pin()
+    }
+
+    fn protect<T>(_guard: &mut Self::Guard, _slot: usize, src: &AtomicPtr<T>) -> *mut T {
+        src.load(Ordering::Acquire)
+    }
+
+    unsafe fn retire_with<T, F>(guard: &Self::Guard, ptr: *mut T, free: F)
+    where
+        F: FnOnce(*mut T) + Send + 'static,
+    {
+        guard.defer_unchecked(move || free(ptr));
+    }
+}
+
+/// Hazard pointers ([`crate::hazard`], домен по умолчанию).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hazard;
+
+/// Guard для [`Hazard`]: `SLOTS` hazard-слотов текущего потока.
+///
+/// Guard'ы можно вкладывать друг в друга без ограничения: когда слоты
+/// потока кончаются, [`hazard::hazard_pointer`] берёт их у нового участника.
+pub struct HazardGuard {
+    slots: [hazard::HazardPointer<'static>; SLOTS],
+}
+
+impl Reclaimer for Hazard {
+    type Guard = HazardGuard;
+
+    fn pin() -> Self::Guard {
+        HazardGuard {
+            slots: array_init::array_init(|_| hazard::hazard_pointer()),
+        }
+    }
+
+    fn protect<T>(guard: &mut Self::Guard, slot: usize, src: &AtomicPtr<T>) -> *mut T {
+        guard.slots[slot].protect(src)
+    }
+
+    unsafe fn retire_with<T, F>(_guard: &Self::Guard, ptr: *mut T, free: F)
+    where
+        F: FnOnce(*mut T) + Send + 'static,
+    {
+        hazard::retire_with(ptr, free);
+    }
+}
+
+/// Схемы на эпохах: пока guard жив, не освобождается ни один узел,
+/// отцеплённый после `pin()`, — а не только защищённые в слотах.
+///
+/// Это позволяет обходить структуру целиком (например,
+/// [`PeekableStack::iter`](crate::treiber_stack::PeekableStack::iter)),
+/// держа один guard.
+///
+/// # Safety
+///
+/// Реализация гарантирует: всё, что было достижимо из структуры, пока
+/// guard жив, освобождается не раньше, чем guard будет отпущен, — если
+/// [`owns_guard`](Self::owns_guard) для него вернул true.
+pub unsafe trait EpochReclaimer: Reclaimer {
+    /// Закреплён ли `guard` в сборщике этой схемы. Guard того же типа
+    /// из другого коллектора не удерживает узлы, отложенные через схему.
+    fn owns_guard(guard: &Self::Guard) -> bool;
+}
+
+unsafe impl EpochReclaimer for Ebr {
+    fn owns_guard(guard: &Self::Guard) -> bool {
+        guard.collector() == ebr::default_collector()
+    }
+}
+
+unsafe impl EpochReclaimer for CrossbeamEpoch {
+    fn owns_guard(guard: &Self::Guard) -> bool {
+        guard.collector() == Some(crossbeam_epoch::default_collector())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    /// Значение, считающее свои дропы.
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Защищаем, отцепляем и откладываем объект; он должен быть
+    /// освобождён ровно один раз (не раньше, чем guard отпущен).
+    fn retire_once<R: Reclaimer>(flush: impl Fn()) {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let src = AtomicPtr::new(Box::into_raw(Box::new(DropCounter(drops.clone()))));
+
+        let mut guard = R::pin();
+        let p = R::protect(&mut guard, 0, &src);
+        assert!(!p.is_null());
+        src.store(ptr::null_mut(), Ordering::Release);
+        unsafe { R::retire(&guard, p) };
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+        drop(guard);
+
+        for _ in 0..1000 {
+            if drops.load(Ordering::Relaxed) == 1 {
+                break;
+            }
+            flush();
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_ebr_retire() {
+        retire_once::<Ebr>(ebr::flush);
+    }
+
+    #[test]
+    fn test_crossbeam_retire() {
+        retire_once::<CrossbeamEpoch>(|| crossbeam_epoch::pin().flush());
+    }
+
+    #[test]
+    fn test_hazard_retire() {
+        retire_once::<Hazard>(hazard::scan);
+    }
+
+    #[test]
+    fn test_nested_hazard_guards() {
+        // Слотов потока хватает на HAZARDS_PER_THREAD / SLOTS guard'ов
+        let outer: Vec<_> = (0..hazard::HAZARDS_PER_THREAD / SLOTS)
+            .map(|_| Hazard::pin())
+            .collect();
+        let drops = Arc::new(AtomicUsize::new(0));
+        let src = AtomicPtr::new(Box::into_raw(Box::new(DropCounter(drops.clone()))));
+
+        // Следующий guard получает слоты нового участника и защищает так же
+        let mut inner = Hazard::pin();
+        let p = Hazard::protect(&mut inner, 0, &src);
+        src.store(ptr::null_mut(), Ordering::Release);
+        drop(outer);
+        unsafe { Hazard::retire(&inner, p) };
+        hazard::scan();
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+        drop(inner);
+        hazard::scan();
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,163 @@
+//! Реестр участников, общий для схем освобождения памяти
+//! ([`crate::ebr`], [`crate::qsbr`], [`crate::hazard`]).
+//!
+//! Каждая схема хранит в слоте своё состояние участника (эпоху, точку
+//! покоя, hazard-указатели), а занятие и освобождение слотов, обход
+//! активных участников и дескриптор потока в коллекторе по умолчанию
+//! (thread_local) устроены одинаково и живут здесь.
+
+use std::{
+    cell::RefCell,
+    sync::atomic::{AtomicBool, Ordering},
+    thread::LocalKey,
+};
+
+/// Максимальное число потоков (участников) в одном коллекторе.
+/// В реальном (промышленном) коде обычно динамически расширяемо
+/// (например, хранится в Vec или hashmap).
+pub const MAX_THREADS: usize = 32;
+
+/// Слот: занят ли он и состояние участника схемы.
+struct Slot<S> {
+    active: AtomicBool,
+    state: S,
+}
+
+/// Фиксированный массив из `MAX_THREADS` слотов участников.
+pub(crate) struct Registry<S> {
+    slots: [Slot<S>; MAX_THREADS],
+}
+
+impl<S> Registry<S> {
+    /// Реестр со свободными слотами; `init` создаёт состояние каждого слота.
+    pub(crate) fn new(mut init: impl FnMut() -> S) -> Self {
+        Registry {
+            slots: array_init::array_init(|_| Slot {
+                active: AtomicBool::new(false),
+                state: init(),
+            }),
+        }
+    }
+
+    /// Занимает свободный слот и возвращает его номер.
+    ///
+    /// # Panics
+    ///
+    /// Если все `MAX_THREADS` слотов заняты; `owner` попадает в сообщение.
+    pub(crate) fn acquire(&self, owner: &str) -> usize {
+        self.slots
+            .iter()
+            .position(|slot| {
+                // Сравнение: active == false => true
+                // Если удалось, значит этот слот "наш".
+                slot.active
+                    .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+            })
+            .unwrap_or_else(|| panic!("No free slot in {owner} (increase MAX_THREADS)"))
+    }
+
+    /// Освобождает слот. Состояние участника к этому моменту должно быть
+    /// сброшено: его ещё могут прочитать те, кто обходит реестр.
+    pub(crate) fn release(&self, index: usize) {
+        self.slots[index].active.store(false, Ordering::Release);
+    }
+
+    /// Состояние участника в слоте `index`.
+    pub(crate) fn get(&self, index: usize) -> &S {
+        &self.slots[index].state
+    }
+
+    /// Занят ли слот `index`.
+    pub(crate) fn is_active(&self, index: usize) -> bool {
+        self.slots[index].active.load(Ordering::Acquire)
+    }
+
+    /// Занятые слоты: номер и состояние участника.
+    pub(crate) fn active(&self) -> impl Iterator<Item = (usize, &S)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.active.load(Ordering::Acquire))
+            .map(|(index, slot)| (index, &slot.state))
+    }
+}
+
+/// Дескриптор потока в коллекторе по умолчанию, хранимый в `key`
+/// (None — поток ещё не зарегистрирован; `register` регистрирует его).
+pub(crate) type ThreadHandle<H> = RefCell<Option<H>>;
+
+/// Вызывает `f` с дескриптором текущего потока, при первом обращении
+/// регистрируя его через `register`.
+pub(crate) fn with_thread_handle<H, R>(
+    key: &'static LocalKey<ThreadHandle<H>>,
+    register: impl Fn() -> H,
+    f: impl Fn(&H) -> R,
+) -> R {
+    key.try_with(|h| {
+        if h.borrow().is_none() {
+            *h.borrow_mut() = Some(register());
+        }
+        // Разделяемое заимствование: `f` может запустить отложенные
+        // функции, которые сами обратятся к коллектору.
+        f(h.borrow().as_ref().unwrap())
+    })
+    // thread_local уже уничтожен (вызов из деструктора другого thread_local):
+    // регистрируемся временно.
+    .unwrap_or_else(|_| f(&register()))
+}
+
+/// Отключает текущий поток: дропает его дескриптор из `key`.
+pub(crate) fn unregister_thread_handle<H>(key: &'static LocalKey<ThreadHandle<H>>) {
+    let _ = key.try_with(|h| h.borrow_mut().take());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_acquire_and_release() {
+        let registry = Registry::new(|| AtomicUsize::new(0));
+        let a = registry.acquire("test");
+        let b = registry.acquire("test");
+        assert_ne!(a, b);
+        registry.get(b).store(7, Ordering::Relaxed);
+
+        let active: Vec<_> = registry
+            .active()
+            .map(|(i, s)| (i, s.load(Ordering::Relaxed)))
+            .collect();
+        assert_eq!(active, vec![(a, 0), (b, 7)]);
+
+        registry.release(a);
+        assert!(!registry.is_active(a));
+        assert!(registry.is_active(b));
+        assert_eq!(registry.acquire("test"), a);
+    }
+
+    #[test]
+    #[should_panic(expected = "No free slot in test registry")]
+    fn test_full_registry_panics() {
+        let registry = Registry::new(|| ());
+        for _ in 0..=MAX_THREADS {
+            registry.acquire("test registry");
+        }
+    }
+
+    thread_local! {
+        static HANDLE: ThreadHandle<usize> = const { RefCell::new(None) };
+    }
+
+    #[test]
+    fn test_thread_handle_registers_once() {
+        let registered = AtomicUsize::new(0);
+        let register = || registered.fetch_add(1, Ordering::Relaxed) + 10;
+        assert_eq!(with_thread_handle(&HANDLE, register, |h| *h), 10);
+        assert_eq!(with_thread_handle(&HANDLE, register, |h| *h), 10);
+
+        unregister_thread_handle(&HANDLE);
+        assert_eq!(with_thread_handle(&HANDLE, register, |h| *h), 11);
+    }
+}
@@ -1,17 +1,22 @@
 use std::cell::UnsafeCell;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+use crate::cache_padded::CachePadded;
+
 /// Lock-free кольцевой буфер с фиксированной ёмкостью.
 /// Эта структура потокобезопасна и может использоваться в многопоточной среде.
 pub struct RingBuffer<T> {
     buffer: Vec<UnsafeCell<Option<T>>>, // Внутренний массив для хранения данных
     size: usize,                        // Фиксированная ёмкость буфера
-    write_index: AtomicUsize,           // Указатель на запись (write head)
-    read_index: AtomicUsize,            // Указатель на чтение (read head)
+    // Индексы в разных кэш-линиях: писатели и читатели не мешают друг другу
+    write_index: CachePadded<AtomicUsize>, // Указатель на запись (write head)
+    read_index: CachePadded<AtomicUsize>,  // Указатель на чтение (read head)
 }
 
 // Объявляем буфер потокобезопасным, так как UnsafeCell по умолчанию не является Sync.
-unsafe impl<T> Sync for RingBuffer<T> {}
+// Элементы передаются между потоками по значению, поэтому нужно T: Send
+// (иначе RingBuffer<Rc<_>> можно было бы разделить между потоками).
+unsafe impl<T: Send> Sync for RingBuffer<T> {}
 
 impl<T> RingBuffer<T> {
     /// Создаёт новый `RingBuffer` заданного размера.
@@ -32,8 +37,8 @@ impl<T> RingBuffer<T> {
         RingBuffer {
             buffer,
             size,
-            write_index: AtomicUsize::new(0), // Начальный индекс записи - 0
-            read_index: AtomicUsize::new(0),  // Начальный индекс чтения - 0
+            write_index: CachePadded::new(AtomicUsize::new(0)), // Начальный индекс записи - 0
+            read_index: CachePadded::new(AtomicUsize::new(0)),  // Начальный индекс чтения - 0
         }
     }
 
@@ -0,0 +1,245 @@
+//! Указатель с версией (tag) для защиты от ABA.
+//!
+//! Классическая ABA в lock-free структурах: поток прочитал `head == A`,
+//! его вытеснили, за это время A сняли, переиспользовали и снова положили
+//! на вершину — и CAS `A → next` проходит, хотя `next` давно устарел.
+//! Если к указателю приклеен счётчик версий, который растёт при каждой
+//! успешной замене, "тот же" A придёт с другой версией, и CAS не пройдёт.
+//!
+//! Версия хранится в неиспользуемых старших битах указателя: на x86_64
+//! и aarch64 пользовательские адреса занимают младшие 48 бит, поэтому
+//! [`TAG_BITS`] старших бит свободны и весь [`TaggedPtr`] умещается в одно
+//! машинное слово — хватает обычного `AtomicPtr` без двойного CAS.
+//! Версия вписывается в адрес через `map_addr`, а не приведением к целому,
+//! поэтому слово сохраняет provenance исходного указателя.
+//! Счётчик конечен: ABA снова возможна, только если между чтением и CAS
+//! пройдёт ровно кратное `2^TAG_BITS` число замен.
+//!
+//! Модуль есть только на 64-битных платформах.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Сколько старших бит указателя занято версией.
+pub const TAG_BITS: u32 = 16;
+
+/// Сдвиг версии.
+const TAG_SHIFT: u32 = usize::BITS - TAG_BITS;
+
+/// Маска адресной части.
+const PTR_MASK: usize = (1 << TAG_SHIFT) - 1;
+
+/// Указатель вместе с версией, упакованные в одно слово.
+pub struct TaggedPtr<T> {
+    data: *mut T,
+}
+
+// derive добавил бы лишние ограничения на T.
+impl<T> Clone for TaggedPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TaggedPtr<T> {}
+
+impl<T> PartialEq for TaggedPtr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data.addr() == other.data.addr()
+    }
+}
+
+impl<T> Eq for TaggedPtr<T> {}
+
+impl<T> fmt::Debug for TaggedPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaggedPtr")
+            .field("ptr", &self.ptr())
+            .field("tag", &self.tag())
+            .finish()
+    }
+}
+
+impl<T> TaggedPtr<T> {
+    /// Упаковывает указатель и версию (берутся младшие `TAG_BITS` бит `tag`).
+    ///
+    /// # Panics
+    ///
+    /// Если указатель занимает биты версии: иначе версия молча испортила бы
+    /// адрес.
+    pub fn new(ptr: *mut T, tag: usize) -> Self {
+        assert_eq!(ptr.addr() & !PTR_MASK, 0, "pointer uses tag bits");
+        TaggedPtr {
+            data: ptr.map_addr(|addr| addr | (tag << TAG_SHIFT)),
+        }
+    }
+
+    /// Нулевой указатель с версией 0.
+    pub fn null() -> Self {
+        Self::new(std::ptr::null_mut(), 0)
+    }
+
+    /// Адресная часть.
+    pub fn ptr(self) -> *mut T {
+        self.data.map_addr(|addr| addr & PTR_MASK)
+    }
+
+    /// Версия.
+    pub fn tag(self) -> usize {
+        self.data.addr() >> TAG_SHIFT
+    }
+
+    /// Нулевой ли указатель (версия не учитывается).
+    pub fn is_null(self) -> bool {
+        self.ptr().is_null()
+    }
+
+    /// Новый указатель со следующей версией — то, что надо записывать
+    /// при каждой успешной замене.
+    pub fn successor(self, ptr: *mut T) -> Self {
+        Self::new(ptr, self.tag().wrapping_add(1))
+    }
+}
+
+/// Атомарный [`TaggedPtr`].
+///
+/// Внутри — `AtomicPtr` с версией в старших битах: как и у `AtomicPtr`,
+/// за безопасность разыменования отвечает владелец структуры.
+pub struct AtomicTaggedPtr<T> {
+    data: AtomicPtr<T>,
+}
+
+impl<T> Default for AtomicTaggedPtr<T> {
+    fn default() -> Self {
+        Self::new(TaggedPtr::null())
+    }
+}
+
+impl<T> fmt::Debug for AtomicTaggedPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+impl<T> AtomicTaggedPtr<T> {
+    pub fn new(value: TaggedPtr<T>) -> Self {
+        AtomicTaggedPtr {
+            data: AtomicPtr::new(value.data),
+        }
+    }
+
+    pub fn load(&self, ord: Ordering) -> TaggedPtr<T> {
+        TaggedPtr {
+            data: self.data.load(ord),
+        }
+    }
+
+    /// Слово при монопольном доступе.
+    pub fn get_mut(&mut self) -> TaggedPtr<T> {
+        TaggedPtr {
+            data: *self.data.get_mut(),
+        }
+    }
+
+    pub fn store(&self, value: TaggedPtr<T>, ord: Ordering) {
+        self.data.store(value.data, ord);
+    }
+
+    pub fn swap(&self, value: TaggedPtr<T>, ord: Ordering) -> TaggedPtr<T> {
+        TaggedPtr {
+            data: self.data.swap(value.data, ord),
+        }
+    }
+
+    /// CAS по указателю и версии сразу.
+    /// При неудаче возвращает актуальное значение.
+    pub fn compare_exchange(
+        &self,
+        current: TaggedPtr<T>,
+        new: TaggedPtr<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<TaggedPtr<T>, TaggedPtr<T>> {
+        self.data
+            .compare_exchange(current.data, new.data, success, failure)
+            .map(|data| TaggedPtr { data })
+            .map_err(|data| TaggedPtr { data })
+    }
+
+    /// Как [`compare_exchange`](Self::compare_exchange), но может ложно
+    /// не сработать (удобно в цикле).
+    pub fn compare_exchange_weak(
+        &self,
+        current: TaggedPtr<T>,
+        new: TaggedPtr<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<TaggedPtr<T>, TaggedPtr<T>> {
+        self.data
+            .compare_exchange_weak(current.data, new.data, success, failure)
+            .map(|data| TaggedPtr { data })
+            .map_err(|data| TaggedPtr { data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack() {
+        let mut x = 5u64;
+        let p = TaggedPtr::new(&mut x as *mut u64, 7);
+        assert_eq!(p.ptr(), &mut x as *mut u64);
+        assert_eq!(p.tag(), 7);
+        assert!(!p.is_null());
+        assert!(TaggedPtr::<u64>::null().is_null());
+    }
+
+    #[test]
+    fn test_tag_wraps() {
+        let p = TaggedPtr::<u8>::new(std::ptr::null_mut(), (1 << TAG_BITS) - 1);
+        let next = p.successor(std::ptr::null_mut());
+        assert_eq!(next.tag(), 0);
+        assert!(next.is_null());
+    }
+
+    #[test]
+    #[should_panic(expected = "pointer uses tag bits")]
+    fn test_pointer_in_tag_bits_panics() {
+        TaggedPtr::new(std::ptr::without_provenance_mut::<u8>(1 << TAG_SHIFT), 0);
+    }
+
+    #[test]
+    fn test_cas_detects_aba() {
+        let mut a = 1u32;
+        let mut b = 2u32;
+        let (a, b) = (&mut a as *mut u32, &mut b as *mut u32);
+        let atomic = AtomicTaggedPtr::new(TaggedPtr::new(a, 0));
+
+        // Снимок до A → B → A
+        let seen = atomic.load(Ordering::Acquire);
+        let mid = seen.successor(b);
+        atomic.store(mid, Ordering::Release);
+        atomic.store(mid.successor(a), Ordering::Release);
+
+        // Указатель тот же, версия другая — CAS не проходит
+        let actual = atomic
+            .compare_exchange(seen, seen.successor(b), Ordering::AcqRel, Ordering::Acquire)
+            .unwrap_err();
+        assert_eq!(actual.ptr(), a);
+        assert_eq!(actual.tag(), 2);
+
+        assert!(atomic
+            .compare_exchange(
+                actual,
+                actual.successor(b),
+                Ordering::AcqRel,
+                Ordering::Acquire
+            )
+            .is_ok());
+        assert_eq!(atomic.swap(TaggedPtr::null(), Ordering::AcqRel).ptr(), b);
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::TreiberStack;
+use crate::reclaim::{Ebr, Reclaimer};
+
+/// TreiberStack с ограниченной ёмкостью (для back-pressure).
+///
+/// Длина ведётся отдельным атомарным счётчиком: push сначала резервирует
+/// место (CAS `len → len + 1`, только если `len < capacity`) и лишь потом
+/// кладёт узел, а pop уменьшает счётчик уже после снятия. Поэтому счётчик
+/// никогда не меньше числа элементов в стеке и ёмкость не превышается,
+/// но [`len`](Self::len) приблизителен: он учитывает push'и, которые
+/// зарезервировали место и ещё не положили узел.
+///
+/// pop остаётся lock-free: стек тот же, счётчик — один `fetch_sub`.
+pub struct BoundedStack<T, R: Reclaimer = Ebr> {
+    stack: TreiberStack<T, R>,
+    len: AtomicUsize,
+    capacity: usize,
+}
+
+impl<T> BoundedStack<T> {
+    /// Создаёт пустой стек (EBR) на `capacity` элементов.
+    /// Для другой схемы: `BoundedStack::<T, R>::with_capacity(capacity)`.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_capacity(capacity)
+    }
+}
+
+impl<T, R: Reclaimer> BoundedStack<T, R> {
+    /// Создаёт пустой стек на `capacity` элементов.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::from_stack(TreiberStack::default(), capacity)
+    }
+
+    /// Ограничивает пустой стек (например, собранный билдером с пулом узлов).
+    ///
+    /// # Panics
+    ///
+    /// Если `stack` не пуст.
+    pub fn from_stack(mut stack: TreiberStack<T, R>, capacity: usize) -> Self {
+        assert!(stack.head.get_mut().is_null(), "stack must be empty");
+        BoundedStack {
+            stack,
+            len: AtomicUsize::new(0),
+            capacity,
+        }
+    }
+
+    /// Добавляет элемент; если стек полон — возвращает его обратно.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        // Резервируем место до публикации узла
+        let reserved = self
+            .len
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |len| {
+                (len < self.capacity).then_some(len + 1)
+            });
+        if reserved.is_err() {
+            return Err(value);
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    /// Снимает верхний элемент.
+    pub fn pop(&self) -> Option<T> {
+        let value = self.stack.pop()?;
+        // Место освобождаем только после снятия
+        self.len.fetch_sub(1, Ordering::Release);
+        Some(value)
+    }
+
+    /// Приблизительное число элементов (с учётом незавершённых push).
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Пуст ли стек (приблизительно, как и [`len`](Self::len)).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Заполнен ли стек (приблизительно).
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.capacity
+    }
+
+    /// Ёмкость стека.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reclaim::Hazard;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_push_rejects_when_full() {
+        let stack = BoundedStack::new(2);
+        assert!(stack.is_empty());
+        assert_eq!(stack.push(1), Ok(()));
+        assert_eq!(stack.push(2), Ok(()));
+        assert!(stack.is_full());
+        assert_eq!(stack.push(3), Err(3));
+        assert_eq!(stack.len(), 2);
+
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.push(4), Ok(()));
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let stack: BoundedStack<i32, Hazard> = BoundedStack::with_capacity(0);
+        assert_eq!(stack.push(1), Err(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_from_pooled_stack() {
+        let stack = BoundedStack::from_stack(TreiberStack::builder().thread_cache(4).build(), 1);
+        assert_eq!(stack.push("a"), Ok(()));
+        assert_eq!(stack.push("b"), Err("b"));
+        assert_eq!(stack.pop(), Some("a"));
+    }
+
+    /// Производители упираются в ёмкость, потребители её освобождают:
+    /// ёмкость не превышается, и всё принятое снимается ровно один раз.
+    #[test]
+    fn test_concurrent_capacity_is_respected() {
+        let capacity = 8;
+        let threads = 4;
+        let per_thread = 500;
+        let stack = Arc::new(BoundedStack::new(capacity));
+
+        let producers: Vec<_> = (0..threads)
+            .map(|t| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || {
+                    let mut accepted = Vec::new();
+                    for i in 0..per_thread {
+                        let value = t * per_thread + i;
+                        if stack.push(value).is_ok() {
+                            accepted.push(value);
+                        }
+                        assert!(stack.len() <= capacity);
+                    }
+                    accepted
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..threads)
+            .map(|_| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for _ in 0..per_thread {
+                        popped.extend(stack.pop());
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut accepted: Vec<_> = producers
+            .into_iter()
+            .flat_map(|p| p.join().unwrap())
+            .collect();
+        let mut popped: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|c| c.join().unwrap())
+            .collect();
+        while let Some(v) = stack.pop() {
+            popped.push(v);
+        }
+        accepted.sort_unstable();
+        popped.sort_unstable();
+        assert_eq!(accepted, popped);
+        assert!(stack.is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+use std::marker::PhantomData;
+
+use super::{PeekableStack, TreiberStack};
+use crate::pool::PoolConfig;
+use crate::reclaim::{Ebr, Reclaimer};
+
+/// Билдер [`TreiberStack`]: схема освобождения памяти, пул узлов и
+/// стек с [`peek`](PeekableStack::peek)/[`iter`](PeekableStack::iter).
+///
+/// Например: `TreiberStack::<u64>::builder().reclaimer::<Hazard>().thread_cache(128).build()`.
+pub struct TreiberStackBuilder<T, R: Reclaimer = Ebr> {
+    pool: Option<PoolConfig>,
+    _marker: PhantomData<fn() -> (T, R)>,
+}
+
+impl<T> TreiberStackBuilder<T> {
+    /// Билдер с настройками по умолчанию: EBR, без пула.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// derive добавил бы лишние ограничения на T и R.
+impl<T, R: Reclaimer> Clone for TreiberStackBuilder<T, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, R: Reclaimer> Copy for TreiberStackBuilder<T, R> {}
+
+impl<T, R: Reclaimer> Default for TreiberStackBuilder<T, R> {
+    fn default() -> Self {
+        TreiberStackBuilder {
+            pool: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, R: Reclaimer> TreiberStackBuilder<T, R> {
+    /// Другая схема освобождения памяти (см. [`crate::reclaim`]).
+    pub fn reclaimer<R2: Reclaimer>(self) -> TreiberStackBuilder<T, R2> {
+        TreiberStackBuilder {
+            pool: self.pool,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Включает пул узлов с настройками `config`.
+    pub fn pool(mut self, config: PoolConfig) -> Self {
+        self.pool = Some(config);
+        self
+    }
+
+    /// Включает пул узлов и задаёт размер кэша потока.
+    pub fn thread_cache(self, n: usize) -> Self {
+        let config = self.pool.unwrap_or_default();
+        self.pool(PoolConfig {
+            thread_cache: n,
+            ..config
+        })
+    }
+
+    /// Включает пул узлов и ограничивает общий пул `n` узлами.
+    pub fn max_pooled(self, n: usize) -> Self {
+        let config = self.pool.unwrap_or_default();
+        self.pool(PoolConfig {
+            max_pooled: n,
+            ..config
+        })
+    }
+
+    /// Создаёт пустой стек.
+    pub fn build(self) -> TreiberStack<T, R> {
+        TreiberStack::with_config(self.pool, None)
+    }
+}
+
+impl<T: Clone, R: Reclaimer> TreiberStackBuilder<T, R> {
+    /// Создаёт пустой стек с [`peek`](PeekableStack::peek) и
+    /// [`iter`](PeekableStack::iter): pop отдаёт клон значения, а само
+    /// значение остаётся в узле и дропается вместе с ним, когда на него
+    /// уже никто не смотрит. Цена — клон на pop.
+    pub fn peekable(self) -> PeekableStack<T, R> {
+        PeekableStack::from_stack(TreiberStack::with_config(self.pool, Some(T::clone)))
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    iter::FusedIterator,
+    marker::PhantomData,
+    ptr,
+    sync::{atomic::Ordering, Arc},
+};
+
+use super::sealed::{AtomicTop, TopWord};
+use super::{retire_node, Node, StackReclaimer, TreiberStack};
+use crate::pool::NodePool;
+use crate::reclaim::{Ebr, Reclaimer};
+
+impl<T, R: Reclaimer> TreiberStack<T, R> {
+    /// Забирает всё содержимое стека одним `swap` и возвращает
+    /// владеющий итератор по отцеплённой цепочке (сверху вниз).
+    ///
+    /// Удобно для free list: вместо n CAS на каждый pop — один swap.
+    pub fn pop_all(&self) -> PopAll<T, R> {
+        let head = self.head.swap(ptr::null_mut(), Ordering::Acquire);
+        PopAll {
+            first: head,
+            head,
+            pool: self.pool.clone(),
+            clone_on_pop: self.clone_on_pop,
+            _reclaimer: PhantomData,
+        }
+    }
+}
+
+impl<T, R: StackReclaimer> TreiberStack<T, R> {
+    /// Кладёт все элементы `iter` одним CAS: сначала строит приватную
+    /// цепочку, потом прицепляет её к вершине.
+    ///
+    /// Порядок тот же, что у последовательных push: последний элемент
+    /// `iter` окажется на вершине. Другие потоки видят либо всю пачку,
+    /// либо ни одного её элемента.
+    pub fn push_list<I: IntoIterator<Item = T>>(&self, iter: I) {
+        let mut iter = iter.into_iter();
+        let Some(first) = iter.next() else {
+            return;
+        };
+
+        // bottom — первый элемент (низ пачки), top — последний
+        let bottom = self.alloc_node(first);
+        let mut top = bottom;
+        for value in iter {
+            let node = self.alloc_node(value);
+            unsafe { (*node).next.store(top, Ordering::Relaxed) };
+            top = node;
+        }
+
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            // Низ пачки указывает на старый head
+            unsafe { (*bottom).next.store(head.node(), Ordering::Relaxed) };
+            if self
+                .head
+                .compare_exchange_weak(
+                    head,
+                    head.successor(top),
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Владеющий итератор по цепочке, снятой [`TreiberStack::pop_all`].
+///
+/// Цепочка уже недостижима через `head`, но конкурентный pop мог успеть
+/// прочитать её верхний узел, поэтому память узлов не освобождается сразу,
+/// а отдаётся схеме `R` — вся цепочка под одним guard'ом, когда итератор
+/// дропается. Невычитанные значения дропаются вместе с итератором.
+pub struct PopAll<T, R: Reclaimer = Ebr> {
+    /// Начало цепочки: отсюда узлы отдаются схеме в Drop.
+    first: *mut Node<T>,
+    /// Следующий невычитанный узел.
+    head: *mut Node<T>,
+    pool: Option<Arc<NodePool>>,
+    clone_on_pop: Option<fn(&T) -> T>,
+    _reclaimer: PhantomData<R>,
+}
+
+// Цепочка принадлежит итератору целиком — его можно передать в другой поток.
+unsafe impl<T: Send, R: Reclaimer> Send for PopAll<T, R> {}
+
+impl<T, R: Reclaimer> Iterator for PopAll<T, R> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.head.is_null() {
+            return None;
+        }
+
+        // Цепочка наша: идём по ней без guard'а, узлы отдаются схеме в Drop
+        let node = self.head;
+        self.head = unsafe { (*node).next.load(Ordering::Relaxed) };
+        // Как у pop: на значения снятой цепочки могут смотреть iter/peek
+        let value = match self.clone_on_pop {
+            Some(clone) => clone(unsafe { &(*node).value }),
+            None => unsafe { Node::take_value(node) },
+        };
+        Some(value)
+    }
+}
+
+impl<T, R: Reclaimer> FusedIterator for PopAll<T, R> {}
+
+impl<T, R: Reclaimer> Drop for PopAll<T, R> {
+    fn drop(&mut self) {
+        // С peek значения остаются в узлах и дропаются вместе с ними
+        if self.clone_on_pop.is_none() {
+            for value in self.by_ref() {
+                drop(value);
+            }
+        }
+        if self.first.is_null() {
+            return;
+        }
+
+        let guard = R::pin();
+        let drop_value = self.clone_on_pop.is_some();
+        let mut node = self.first;
+        while !node.is_null() {
+            // next читаем до retire: без защиты узел может освободиться сразу
+            let next = unsafe { (*node).next.load(Ordering::Relaxed) };
+            unsafe { retire_node::<T, R>(&self.pool, &guard, node, drop_value) };
+            node = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_push_list_keeps_push_order() {
+        let stack = TreiberStack::new();
+        stack.push(0);
+        stack.push_list(1..=3);
+        stack.push_list(Vec::new());
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), Some(0));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_pop_all() {
+        let stack = TreiberStack::new();
+        assert_eq!(stack.pop_all().next(), None);
+
+        stack.push_list([1, 2, 3]);
+        stack.push(4);
+        assert_eq!(stack.pop_all().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_partially_consumed_pop_all_drops_rest() {
+        struct DropCounter(Arc<AtomicUsize>);
+
+        impl Drop for DropCounter {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let stack = TreiberStack::new();
+        stack.push_list((0..5).map(|_| DropCounter(drops.clone())));
+
+        let mut all = stack.pop_all();
+        drop(all.next());
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        drop(all);
+        assert_eq!(drops.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn test_pop_all_retires_chain_on_drop() {
+        let stack = TreiberStack::<u64>::builder()
+            .reclaimer::<crate::reclaim::Hazard>()
+            .thread_cache(8)
+            .build();
+        stack.push_list(0..4);
+
+        let mut all = stack.pop_all();
+        assert_eq!(all.next(), Some(3));
+        assert_eq!(all.next(), Some(2));
+        // Вычитанные узлы ждут Drop итератора
+        crate::hazard::scan();
+        assert_eq!(stack.pooled(), 0);
+
+        drop(all);
+        crate::hazard::scan();
+        assert_eq!(stack.pooled(), 4);
+    }
+
+    #[test]
+    fn test_concurrent_batches() {
+        let threads = if cfg!(miri) { 2 } else { 4 };
+        let batches = if cfg!(miri) { 10 } else { 500 };
+        let batch = 8;
+        let stack = Arc::new(TreiberStack::new());
+
+        // Производители кладут пачками, потребители забирают всё сразу
+        let producers: Vec<_> = (0..threads)
+            .map(|t| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || {
+                    for b in 0..batches {
+                        let start = (t * batches + b) * batch;
+                        stack.push_list(start..start + batch);
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..threads)
+            .map(|_| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || {
+                    let mut taken = Vec::new();
+                    for _ in 0..batches {
+                        taken.extend(stack.pop_all());
+                        taken.extend(stack.pop());
+                    }
+                    taken
+                })
+            })
+            .collect();
+
+        for p in producers {
+            p.join().unwrap();
+        }
+        let mut values: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|c| c.join().unwrap())
+            .collect();
+        values.extend(stack.pop_all());
+        values.sort_unstable();
+        assert_eq!(values, (0..threads * batches * batch).collect::<Vec<_>>());
+    }
+}
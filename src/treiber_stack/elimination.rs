@@ -0,0 +1,387 @@
+use std::{
+    cell::Cell,
+    hint,
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+    thread,
+};
+
+use super::{Node, TreiberStack};
+use crate::reclaim::{Ebr, Reclaimer};
+
+/// Максимальное число слотов в массиве исключения.
+pub const MAX_SLOTS: usize = 32;
+
+/// Сколько итераций ждём партнёра в слоте.
+const SPINS: usize = 128;
+
+/// TreiberStack со слоем исключения (elimination backoff).
+///
+/// При высокой конкуренции все push/pop бьются за один CAS на `head`.
+/// Проиграв гонку, операция не повторяет её сразу, а идёт в случайный
+/// слот массива исключения: push оставляет там свой узел, pop забирает
+/// его — и обе возвращаются, не трогая `head`. Пара push+pop, встретившихся
+/// так, линеаризуется как push сразу за которым идёт pop.
+///
+/// Рабочая ширина массива подстраивается: если партнёра не дождались,
+/// диапазон сужается (встретиться проще), если слот был занят или
+/// предложение перехватили — расширяется.
+pub struct EliminationStack<T, R: Reclaimer = Ebr> {
+    stack: TreiberStack<T, R>,
+    elimination: EliminationArray<T>,
+}
+
+impl<T> EliminationStack<T> {
+    /// Создаёт пустой стек (EBR) с массивом исключения по числу ядер.
+    /// Для другой схемы: `EliminationStack::<T, R>::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, R: Reclaimer> Default for EliminationStack<T, R> {
+    fn default() -> Self {
+        let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_capacity(cpus)
+    }
+}
+
+impl<T, R: Reclaimer> EliminationStack<T, R> {
+    /// Создаёт пустой стек с массивом исключения на `slots` слотов
+    /// (от 1 до [`MAX_SLOTS`]).
+    pub fn with_capacity(slots: usize) -> Self {
+        Self::with_stack(TreiberStack::default(), slots)
+    }
+
+    /// Добавляет слой исключения к готовому стеку — например, к стеку
+    /// с пулом узлов из [`TreiberStack::builder`]: узлы берутся из его пула
+    /// и возвращаются туда же, в том числе после обмена через массив.
+    pub fn with_stack(stack: TreiberStack<T, R>, slots: usize) -> Self {
+        EliminationStack {
+            stack,
+            elimination: EliminationArray::new(slots.clamp(1, MAX_SLOTS)),
+        }
+    }
+
+    /// Добавляет элемент: сначала в стек, при конфликте — через массив исключения.
+    pub fn push(&self, value: T) {
+        let node = self.stack.alloc_node(value);
+        loop {
+            if self.stack.try_push_node(node) || self.elimination.offer(node) {
+                return;
+            }
+        }
+    }
+
+    /// Снимает элемент: сначала со стека, при конфликте — из массива исключения.
+    pub fn pop(&self) -> Option<T> {
+        let mut guard = R::pin();
+        loop {
+            match self.stack.try_pop(&mut guard) {
+                Ok(value) => return value,
+                Err(()) => {
+                    if let Some(node) = self.elimination.take() {
+                        // Узел наш целиком: его не видел никто, кроме push
+                        let value = unsafe { Node::take_value(node) };
+                        match &self.stack.pool {
+                            Some(pool) => unsafe { pool.recycle(node) },
+                            None => drop(unsafe { Box::from_raw(node) }),
+                        }
+                        return Some(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Сколько слотов массива исключения сейчас используется.
+    pub fn width(&self) -> usize {
+        self.elimination.width.load(Ordering::Relaxed)
+    }
+
+    /// Размер массива исключения.
+    pub fn capacity(&self) -> usize {
+        self.elimination.slots.len()
+    }
+}
+
+/// Слот массива исключения: предложенный узел, null (пусто) или метка `taken()`.
+/// Выравнивание по кэш-линии, чтобы соседние слоты не мешали друг другу.
+#[repr(align(64))]
+struct Slot<T> {
+    offer: AtomicPtr<Node<T>>,
+}
+
+/// Массив исключения: push оставляет узел в слоте, pop его забирает.
+///
+/// Протокол слота:
+/// 1) null → узел — push предложил свой узел и ждёт;
+/// 2) узел → `taken()` — pop забрал узел (теперь он владеет им);
+/// 3) `taken()` → null или узел → null — push освобождает слот (только он).
+///
+/// Пока слот не вернулся в null, туда не попадёт другой узел,
+/// поэтому ABA при отзыве предложения невозможно.
+struct EliminationArray<T> {
+    slots: Box<[Slot<T>]>,
+    width: AtomicUsize,
+}
+
+impl<T> EliminationArray<T> {
+    fn new(capacity: usize) -> Self {
+        EliminationArray {
+            slots: (0..capacity)
+                .map(|_| Slot {
+                    offer: AtomicPtr::new(ptr::null_mut()),
+                })
+                .collect(),
+            width: AtomicUsize::new(capacity.div_ceil(2)),
+        }
+    }
+
+    /// Метка "узел забран": выровненный висячий указатель, который
+    /// не совпадает ни с одним настоящим узлом.
+    fn taken() -> *mut Node<T> {
+        NonNull::dangling().as_ptr()
+    }
+
+    /// Случайный слот в рабочем диапазоне.
+    fn slot(&self) -> &Slot<T> {
+        let width = self.width.load(Ordering::Relaxed);
+        &self.slots[random() as usize % width]
+    }
+
+    fn grow(&self) {
+        let width = self.width.load(Ordering::Relaxed);
+        if width < self.slots.len() {
+            let _ =
+                self.width
+                    .compare_exchange(width, width + 1, Ordering::Relaxed, Ordering::Relaxed);
+        }
+    }
+
+    fn shrink(&self) {
+        let width = self.width.load(Ordering::Relaxed);
+        if width > 1 {
+            let _ =
+                self.width
+                    .compare_exchange(width, width - 1, Ordering::Relaxed, Ordering::Relaxed);
+        }
+    }
+
+    /// push: предлагает узел и ждёт, пока его заберут.
+    /// true — узел забрал pop; false — узел остался нашим.
+    fn offer(&self, node: *mut Node<T>) -> bool {
+        let slot = self.slot();
+        if slot
+            .offer
+            .compare_exchange(ptr::null_mut(), node, Ordering::Release, Ordering::Relaxed)
+            .is_err()
+        {
+            // Слот занят — массив тесен
+            self.grow();
+            return false;
+        }
+
+        for _ in 0..SPINS {
+            if slot.offer.load(Ordering::Acquire) != node {
+                // Забрали: освобождаем слот для следующих
+                slot.offer.store(ptr::null_mut(), Ordering::Release);
+                return true;
+            }
+            hint::spin_loop();
+        }
+
+        // Партнёр не пришёл — отзываем предложение
+        match slot.offer.compare_exchange(
+            node,
+            ptr::null_mut(),
+            Ordering::Relaxed,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => {
+                self.shrink();
+                false
+            }
+            Err(_) => {
+                // Успели забрать в последний момент
+                slot.offer.store(ptr::null_mut(), Ordering::Release);
+                true
+            }
+        }
+    }
+
+    /// pop: ждёт предложение в случайном слоте и забирает узел.
+    fn take(&self) -> Option<*mut Node<T>> {
+        let slot = self.slot();
+        for _ in 0..SPINS {
+            let node = slot.offer.load(Ordering::Acquire);
+            if !node.is_null() && node != Self::taken() {
+                return match slot.offer.compare_exchange(
+                    node,
+                    Self::taken(),
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                ) {
+                    // Узел наш целиком: push больше его не трогает
+                    Ok(_) => Some(node),
+                    Err(_) => {
+                        // Перехватил другой pop
+                        self.grow();
+                        None
+                    }
+                };
+            }
+            hint::spin_loop();
+        }
+        self.shrink();
+        None
+    }
+}
+
+// Потоковый xorshift для выбора слота: дёшево и без общих счётчиков.
+thread_local! {
+    static RNG: Cell<u32> = const { Cell::new(0) };
+}
+
+fn random() -> u32 {
+    RNG.with(|rng| {
+        let mut x = rng.get();
+        if x == 0 {
+            // Засеваем адресом thread_local — у каждого потока свой
+            x = (rng as *const Cell<u32> as usize as u32) | 1;
+        }
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        rng.set(x);
+        x
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_single_thread() {
+        let stack = EliminationStack::new();
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_exchange_through_array() {
+        let array = EliminationArray::new(1);
+        let slot = &array.slots[0].offer;
+
+        // Партнёра нет: предложение отозвано, узел остался нашим
+        let node = Node::alloc(42);
+        assert!(!array.offer(node));
+        assert!(slot.load(Ordering::Relaxed).is_null());
+
+        // Узел в слоте (как будто push ждёт) — pop забирает его
+        slot.store(node, Ordering::Release);
+        assert_eq!(array.take(), Some(node));
+        assert_eq!(
+            slot.load(Ordering::Relaxed),
+            EliminationArray::<i32>::taken()
+        );
+
+        // Пока push не освободил слот, новое предложение туда не попадёт
+        let other = Node::alloc(7);
+        assert!(!array.offer(other));
+        slot.store(ptr::null_mut(), Ordering::Release);
+        assert!(array.take().is_none());
+
+        unsafe {
+            for node in [node, other] {
+                Node::take_value(node);
+                drop(Box::from_raw(node));
+            }
+        }
+    }
+
+    #[test]
+    fn test_width_stays_in_bounds() {
+        let stack: EliminationStack<usize> = EliminationStack::with_capacity(4);
+        assert_eq!(stack.capacity(), 4);
+        assert_eq!(stack.width(), 2);
+
+        // Одиночные попытки без партнёра сужают диапазон до 1
+        for _ in 0..10 {
+            assert!(stack.elimination.take().is_none());
+        }
+        assert_eq!(stack.width(), 1);
+        for _ in 0..10 {
+            stack.elimination.grow();
+        }
+        assert_eq!(stack.width(), 4);
+        assert_eq!(EliminationStack::<u8>::with_capacity(0).capacity(), 1);
+    }
+
+    #[test]
+    fn test_with_stack_uses_pool() {
+        let inner = TreiberStack::<u64>::builder()
+            .reclaimer::<crate::reclaim::Hazard>()
+            .thread_cache(8)
+            .build();
+        let stack = EliminationStack::with_stack(inner, 2);
+        for i in 0..4 {
+            stack.push(i);
+        }
+        while stack.pop().is_some() {}
+        crate::hazard::scan();
+        assert_eq!(stack.stack.pooled(), 4);
+
+        // push берёт узел из пула внутреннего стека
+        stack.push(10);
+        assert_eq!(stack.stack.pooled(), 3);
+        assert_eq!(stack.pop(), Some(10));
+    }
+
+    fn concurrent_push_pop<R: Reclaimer>(stack: EliminationStack<usize, R>, threads: usize) {
+        let iterations = if cfg!(miri) { 50 } else { 5000 };
+        let stack = Arc::new(stack);
+
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for i in 0..iterations {
+                        stack.push(t * iterations + i);
+                        popped.extend(stack.pop());
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut values: Vec<_> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        while let Some(v) = stack.pop() {
+            values.push(v);
+        }
+        values.sort_unstable();
+        assert_eq!(values, (0..threads * iterations).collect::<Vec<_>>());
+        assert!((1..=stack.capacity()).contains(&stack.width()));
+    }
+
+    #[test]
+    fn test_concurrent_push_pop() {
+        let threads = if cfg!(miri) { 3 } else { 8 };
+        concurrent_push_pop::<Ebr>(EliminationStack::with_capacity(threads / 2), threads);
+        // Обмен через массив возвращает узлы в пул внутреннего стека
+        let inner = TreiberStack::builder().thread_cache(16).build();
+        concurrent_push_pop(EliminationStack::with_stack(inner, threads / 2), threads);
+    }
+}
@@ -0,0 +1,243 @@
+use std::{iter::FusedIterator, marker::PhantomData, ops::Deref, sync::atomic::Ordering};
+
+use super::sealed::{AtomicTop, TopWord};
+use super::{Node, StackReclaimer, TreiberStack};
+use crate::reclaim::{Ebr, EpochReclaimer, Reclaimer};
+
+impl<T, R: StackReclaimer> TreiberStack<T, R> {
+    /// Пуст ли стек в момент вызова.
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).node().is_null()
+    }
+}
+
+/// [`TreiberStack`] с чтением без снятия (см.
+/// [`TreiberStackBuilder::peekable`](super::TreiberStackBuilder::peekable)).
+///
+/// Ссылки из [`peek`](Self::peek)/[`iter`](Self::iter) ведут прямо в узлы,
+/// поэтому pop здесь отдаёт клон, а значение в узле живёт, пока узел
+/// кому-то виден. Остальные операции — как у [`TreiberStack`] (через `Deref`).
+pub struct PeekableStack<T, R: Reclaimer = Ebr> {
+    stack: TreiberStack<T, R>,
+}
+
+impl<T, R: Reclaimer> PeekableStack<T, R> {
+    /// Оборачивает стек, собранный с клоном на pop.
+    pub(super) fn from_stack(stack: TreiberStack<T, R>) -> Self {
+        debug_assert!(stack.clone_on_pop.is_some());
+        PeekableStack { stack }
+    }
+}
+
+impl<T, R: Reclaimer> Deref for PeekableStack<T, R> {
+    type Target = TreiberStack<T, R>;
+
+    fn deref(&self) -> &TreiberStack<T, R> {
+        &self.stack
+    }
+}
+
+/// Чтение без снятия. Доступно только для схем на эпохах: hazard pointers
+/// защищают лишь пару узлов, а обход держит ссылки на всю цепочку.
+/// `T: Sync` — потому что одно значение читают несколько потоков.
+impl<T: Sync, R: EpochReclaimer> PeekableStack<T, R> {
+    /// Верхний элемент без снятия; ссылка живёт, пока жив `guard`.
+    ///
+    /// # Panics
+    ///
+    /// Как у [`iter`](Self::iter).
+    pub fn peek<'g>(&'g self, guard: &'g R::Guard) -> Option<&'g T> {
+        self.iter(guard).next()
+    }
+
+    /// Итератор сверху вниз по снимку стека на момент вызова.
+    ///
+    /// Узлы после публикации не меняют `next`, а снятые не освобождаются,
+    /// пока жив `guard`, поэтому обход видит ровно ту цепочку, что была
+    /// под `head` при вызове, независимо от конкурентных push/pop.
+    ///
+    /// # Panics
+    ///
+    /// Если `guard` закреплён не в сборщике схемы `R` (например, в отдельном
+    /// [`Collector`](crate::ebr::Collector)): такой guard не удерживает узлы
+    /// этого стека.
+    pub fn iter<'g>(&'g self, guard: &'g R::Guard) -> Iter<'g, T> {
+        assert!(
+            R::owns_guard(guard),
+            "guard is pinned in a foreign collector"
+        );
+        Iter {
+            cur: self.stack.head.load(Ordering::Acquire),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, R: EpochReclaimer> TreiberStack<T, R> {
+    /// Число элементов в снимке стека (обход цепочки, O(n)).
+    /// При конкурентных push/pop — приблизительно.
+    pub fn len(&self) -> usize {
+        let _guard = R::pin();
+        let mut n = 0;
+        let mut cur = self.head.load(Ordering::Acquire);
+        while !cur.is_null() {
+            n += 1;
+            cur = unsafe { (*cur).next.load(Ordering::Relaxed) }; // Узел защищён закреплением
+        }
+        n
+    }
+}
+
+/// Итератор по снимку стека, см. [`PeekableStack::iter`].
+pub struct Iter<'g, T> {
+    cur: *const Node<T>,
+    _marker: PhantomData<&'g T>,
+}
+
+impl<'g, T> Iterator for Iter<'g, T> {
+    type Item = &'g T;
+
+    fn next(&mut self) -> Option<&'g T> {
+        if self.cur.is_null() {
+            return None;
+        }
+        // Узел не освободится, пока жив guard, к которому привязан 'g
+        let node = unsafe { &*self.cur };
+        self.cur = node.next.load(Ordering::Relaxed);
+        Some(&node.value)
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ebr::{self, Collector};
+    use crate::reclaim::{CrossbeamEpoch, Ebr, Reclaimer};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_peek_and_len() {
+        let stack = TreiberStack::builder().peekable();
+        assert!(stack.is_empty());
+        assert_eq!(stack.len(), 0);
+
+        stack.push(1);
+        stack.push(2);
+        let guard = Ebr::pin();
+        assert_eq!(stack.peek(&guard), Some(&2));
+        assert_eq!(stack.len(), 2);
+        assert!(!stack.is_empty());
+    }
+
+    #[test]
+    fn test_iter_is_snapshot() {
+        let stack = TreiberStack::<u32>::builder()
+            .reclaimer::<CrossbeamEpoch>()
+            .peekable();
+        stack.push_list([1, 2, 3]);
+
+        let guard = CrossbeamEpoch::pin();
+        let mut iter = stack.iter(&guard);
+        assert_eq!(iter.next(), Some(&3));
+
+        // Изменения после начала обхода снимок не трогают
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        stack.push(4);
+        assert_eq!(iter.collect::<Vec<_>>(), vec![&2, &1]);
+
+        assert_eq!(stack.iter(&guard).copied().collect::<Vec<_>>(), vec![4, 1]);
+    }
+
+    /// Читатели обходят стек, пока писатели его меняют: каждый снимок —
+    /// убывающая цепочка (push кладёт возрастающие значения).
+    #[test]
+    fn test_concurrent_iteration() {
+        let stack = Arc::new(TreiberStack::builder().peekable());
+        let iterations = 1000;
+
+        let writer = {
+            let stack = Arc::clone(&stack);
+            thread::spawn(move || {
+                for i in 0..iterations {
+                    stack.push(i);
+                    if i % 3 == 0 {
+                        stack.pop();
+                    }
+                }
+            })
+        };
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || {
+                    for _ in 0..iterations / 10 {
+                        let guard = Ebr::pin();
+                        let snapshot: Vec<_> = stack.iter(&guard).copied().collect();
+                        assert!(snapshot.windows(2).all(|w| w[0] > w[1]));
+                    }
+                })
+            })
+            .collect();
+
+        writer.join().unwrap();
+        for r in readers {
+            r.join().unwrap();
+        }
+    }
+
+    /// Значение, на которое смотрит peek, переживает конкурентный pop:
+    /// pop отдаёт клон, а строка в узле дропается только после guard'а.
+    #[test]
+    fn test_peek_survives_pop_of_non_copy_value() {
+        let stack = TreiberStack::<String>::builder().peekable();
+        stack.push("bottom".to_string());
+        stack.push("top".to_string());
+
+        let guard = Ebr::pin();
+        let top = stack.peek(&guard).unwrap();
+        let popped = thread::scope(|s| s.spawn(|| stack.pop()).join().unwrap());
+        assert_eq!(popped.as_deref(), Some("top"));
+        drop(popped);
+        ebr::flush();
+        assert_eq!(top, "top");
+        drop(guard);
+    }
+
+    /// Значение в узле дропается ровно один раз — вместе с узлом.
+    #[test]
+    fn test_peekable_pop_drops_stored_value() {
+        let value = Arc::new(());
+        let stack = TreiberStack::<Arc<()>>::builder().peekable();
+        stack.push_list([Arc::clone(&value), Arc::clone(&value)]);
+
+        drop(stack.pop());
+        drop(stack.pop_all());
+        // Узлы отложены: значения в них ещё живы
+        ebr::flush();
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    /// Guard из другого коллектора не удерживает узлы стека.
+    #[test]
+    #[should_panic(expected = "foreign collector")]
+    fn test_peek_with_foreign_ebr_guard_panics() {
+        let stack = TreiberStack::<u32>::builder().peekable();
+        // Утечка намеренная: Ebr::Guard — Guard<'static>
+        let guard = Box::leak(Box::new(Collector::new().register())).pin();
+        let _ = stack.peek(&guard);
+    }
+
+    #[test]
+    #[should_panic(expected = "foreign collector")]
+    fn test_peek_with_foreign_crossbeam_guard_panics() {
+        let stack = TreiberStack::<u32>::builder()
+            .reclaimer::<CrossbeamEpoch>()
+            .peekable();
+        let handle = crossbeam_epoch::Collector::new().register();
+        let _ = stack.peek(&handle.pin());
+    }
+}
@@ -0,0 +1,641 @@
+use std::marker::PhantomData;
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Arc;
+
+use crate::pool::{NodePool, PoolConfig};
+use crate::reclaim::{Ebr, Reclaimer};
+use sealed::{AtomicTop, TopWord};
+// use std::thread;
+// use std::sync::Arc;
+
+mod bounded;
+mod builder;
+mod bulk;
+mod elimination;
+mod iter;
+#[cfg(target_pointer_width = "64")]
+mod tagged;
+
+pub use bounded::BoundedStack;
+pub use builder::TreiberStackBuilder;
+pub use bulk::PopAll;
+pub use elimination::EliminationStack;
+pub use iter::{Iter, PeekableStack};
+#[cfg(target_pointer_width = "64")]
+pub use tagged::Tagged;
+
+/// Узел стека (односвязный список)
+pub(crate) struct Node<T> {
+    // Значение забирает pop() через ptr::read, а сам узел освобождается
+    // позже через Reclaimer::retire — поэтому повторно его не дропаем.
+    value: ManuallyDrop<T>,
+    // Указатель на следующий узел. Атомарный: в режиме [`Tagged`] pop может
+    // прочитать next узла, который другой поток в этот момент переиспользует
+    // (значение отбросит CAS по версии).
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    /// Новый узел в куче (ещё не опубликованный).
+    pub(crate) fn alloc(value: T) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            value: ManuallyDrop::new(value),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+
+    /// Забирает значение из узла; память узла остаётся за вызывающим.
+    ///
+    /// # Safety
+    ///
+    /// Значение забирается ровно один раз, и узел принадлежит нам.
+    pub(crate) unsafe fn take_value(node: *mut Self) -> T {
+        ptr::read(&*(*node).value)
+    }
+}
+
+/// Lock-Free стек (Treiber Stack)
+///
+/// Память снятых узлов освобождается схемой `R` (см. [`crate::reclaim`];
+/// по умолчанию — EBR этого крейта, [`crate::ebr`]): pop() закрепляется
+/// перед разыменованием head, а снятый узел не освобождается сразу, а
+/// откладывается. Поэтому конкурентный pop не прочитает освобождённую память,
+/// а узел не может быть переиспользован под тот же адрес (ABA), пока его
+/// кто-то видит.
+///
+/// push() не закрепляется: он разыменовывает только свой, ещё не
+/// опубликованный узел.
+///
+/// Со включённым пулом узлов (см. [`TreiberStack::builder`]) push берёт
+/// узел из кэша потока, а pop отдаёт снятый узел схеме `R` через
+/// `retire_with`: когда узел никому не виден, он возвращается в пул,
+/// а не аллокатору.
+///
+/// На 64-битных платформах вместо схемы освобождения можно взять
+/// [`Tagged`]: узлы живут до дропа стека, а от ABA защищает версия в head.
+pub struct TreiberStack<T, R: StackReclaimer = Ebr> {
+    // Верхний элемент стека: `AtomicPtr`, а для [`Tagged`] —
+    // `AtomicTaggedPtr` с версией (см. [`StackReclaimer`])
+    head: R::Top<T>,
+    pool: Option<Arc<NodePool>>,
+    // Список свободных узлов; непуст только у [`Tagged`]
+    free: R::Top<T>,
+    // Есть у стеков с peek/iter (см. [`PeekableStack`]): pop
+    // отдаёт клон, а значение в узле дропается вместе с узлом
+    clone_on_pop: Option<fn(&T) -> T>,
+    _marker: PhantomData<(*mut T, R)>, // Send/Sync задаём вручную ниже
+}
+
+// Значения переходят между потоками через стек; общие ссылки на них
+// (peek/iter) отдельно требуют `T: Sync`, так что здесь хватает T: Send.
+unsafe impl<T: Send, R: StackReclaimer> Send for TreiberStack<T, R> {}
+unsafe impl<T: Send, R: StackReclaimer> Sync for TreiberStack<T, R> {}
+
+/// Как [`TreiberStack`] снимает узлы, не читая освобождённую память и не
+/// попадаясь на ABA: через схему освобождения памяти (любой [`Reclaimer`])
+/// или через версию в head ([`Tagged`], только на 64-битных платформах).
+///
+/// Реализации закрыты: трейт работает с внутренними узлами стека.
+#[allow(private_bounds)] // закрытый супертрейт — намеренно
+pub trait StackReclaimer: sealed::StackReclaimer {}
+
+impl<R: sealed::StackReclaimer> StackReclaimer for R {}
+
+mod sealed {
+    use std::sync::{atomic::Ordering, Arc};
+
+    use super::Node;
+    use crate::pool::NodePool;
+
+    /// Слово вершины стека, которое загружают и подставляют в CAS.
+    pub(crate) trait TopWord<N>: Copy {
+        /// Узел, на который указывает слово.
+        fn node(self) -> *mut N;
+
+        /// Слово, которым `node` заменяет на вершине это слово.
+        fn successor(self, node: *mut N) -> Self;
+    }
+
+    /// Атомарная вершина стека: head или список свободных узлов.
+    pub(crate) trait AtomicTop<N> {
+        type Word: TopWord<N>;
+
+        fn null() -> Self;
+
+        fn load(&self, ord: Ordering) -> Self::Word;
+
+        fn compare_exchange_weak(
+            &self,
+            current: Self::Word,
+            new: Self::Word,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<Self::Word, Self::Word>;
+
+        /// Узел на вершине при монопольном доступе.
+        fn node_mut(&mut self) -> *mut N;
+    }
+
+    /// Слово вершины стека со схемой `R`.
+    pub(crate) type Word<T, R> = <<R as StackReclaimer>::Top<T> as AtomicTop<Node<T>>>::Word;
+
+    /// Операции, которыми [`TreiberStack`](super::TreiberStack) работает
+    /// с вершиной: для схем освобождения она — просто `AtomicPtr`.
+    pub(crate) trait StackReclaimer: Send + Sync + 'static {
+        type Guard;
+
+        /// Тип head и списка свободных узлов.
+        type Top<T>: AtomicTop<Node<T>>;
+
+        fn pin() -> Self::Guard;
+
+        /// Загружает слово `head`; узел под ним не освободится, пока жив
+        /// `guard` (или до следующего protect).
+        fn protect<T>(guard: &mut Self::Guard, head: &Self::Top<T>) -> Word<T, Self>;
+
+        /// Новый, ещё не опубликованный узел со значением `value`.
+        fn alloc<T>(pool: &Option<Arc<NodePool>>, free: &Self::Top<T>, value: T) -> *mut Node<T>;
+
+        /// Отдаёт снятый узел. `drop_value` — значение осталось в узле
+        /// и дропается вместе с ним, иначе оно уже забрано.
+        ///
+        /// # Safety
+        ///
+        /// Узел снят с head нами и больше нигде не опубликован.
+        unsafe fn retire<T>(
+            pool: &Option<Arc<NodePool>>,
+            free: &Self::Top<T>,
+            guard: &Self::Guard,
+            node: *mut Node<T>,
+            drop_value: bool,
+        );
+    }
+}
+
+impl<N> TopWord<N> for *mut N {
+    fn node(self) -> *mut N {
+        self
+    }
+
+    fn successor(self, node: *mut N) -> Self {
+        node
+    }
+}
+
+impl<N> AtomicTop<N> for AtomicPtr<N> {
+    type Word = *mut N;
+
+    fn null() -> Self {
+        AtomicPtr::new(ptr::null_mut())
+    }
+
+    fn load(&self, ord: Ordering) -> *mut N {
+        AtomicPtr::load(self, ord)
+    }
+
+    fn compare_exchange_weak(
+        &self,
+        current: *mut N,
+        new: *mut N,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut N, *mut N> {
+        AtomicPtr::compare_exchange_weak(self, current, new, success, failure)
+    }
+
+    fn node_mut(&mut self) -> *mut N {
+        *self.get_mut()
+    }
+}
+
+impl<R: Reclaimer> sealed::StackReclaimer for R {
+    type Guard = R::Guard;
+    type Top<T> = AtomicPtr<Node<T>>;
+
+    fn pin() -> Self::Guard {
+        R::pin()
+    }
+
+    fn protect<T>(guard: &mut Self::Guard, head: &AtomicPtr<Node<T>>) -> *mut Node<T> {
+        R::protect(guard, 0, head)
+    }
+
+    /// Новый узел — из пула, если он включён.
+    fn alloc<T>(
+        pool: &Option<Arc<NodePool>>,
+        _free: &AtomicPtr<Node<T>>,
+        value: T,
+    ) -> *mut Node<T> {
+        match pool {
+            Some(pool) => pool.alloc(Node {
+                value: ManuallyDrop::new(value),
+                next: AtomicPtr::new(ptr::null_mut()),
+            }),
+            None => Node::alloc(value),
+        }
+    }
+
+    unsafe fn retire<T>(
+        pool: &Option<Arc<NodePool>>,
+        _free: &AtomicPtr<Node<T>>,
+        guard: &Self::Guard,
+        node: *mut Node<T>,
+        drop_value: bool,
+    ) {
+        retire_node::<T, R>(pool, guard, node, drop_value)
+    }
+}
+
+impl<T> TreiberStack<T> {
+    /// Создаёт новый пустой стек (EBR).
+    /// Для другой схемы: `TreiberStack::<T, R>::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Билдер стека: схема освобождения памяти, пул узлов, peek/iter.
+    pub fn builder() -> TreiberStackBuilder<T> {
+        TreiberStackBuilder::new()
+    }
+}
+
+impl<T, R: StackReclaimer> Default for TreiberStack<T, R> {
+    /// Создаёт новый пустой стек
+    fn default() -> Self {
+        Self::with_config(None, None)
+    }
+}
+
+impl<T, R: StackReclaimer> TreiberStack<T, R> {
+    fn with_config(pool: Option<PoolConfig>, clone_on_pop: Option<fn(&T) -> T>) -> Self {
+        TreiberStack {
+            head: AtomicTop::null(), // Начальный стек пуст
+            pool: pool.map(|config| Arc::new(NodePool::new::<Node<T>>(config))),
+            free: AtomicTop::null(),
+            clone_on_pop,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Добавляет элемент в стек (lock-free push)
+    pub fn push(&self, value: T) {
+        let new_node = self.alloc_node(value);
+        while !self.try_push_node(new_node) {}
+    }
+
+    /// Сколько свободных узлов ждут переиспользования: в пуле или, для
+    /// [`Tagged`], в списке свободных (0, если пул выключен).
+    /// При конкуренции — приблизительно.
+    pub fn pooled(&self) -> usize {
+        let mut n = self.pool.as_ref().map_or(0, |pool| pool.pooled());
+        let mut cur = self.free.load(Ordering::Acquire).node();
+        while !cur.is_null() {
+            n += 1;
+            cur = unsafe { (*cur).next.load(Ordering::Acquire) };
+        }
+        n
+    }
+
+    /// Новый узел: из пула или списка свободных, если они есть.
+    fn alloc_node(&self, value: T) -> *mut Node<T> {
+        R::alloc(&self.pool, &self.free, value)
+    }
+
+    /// Удаляет и возвращает верхний элемент из стека (lock-free pop)
+    pub fn pop(&self) -> Option<T> {
+        let mut guard = R::pin();
+        loop {
+            if let Ok(value) = self.try_pop(&mut guard) {
+                return value;
+            }
+        }
+    }
+
+    /// Одна попытка вставить готовый узел. false — head поменялся, узел остаётся нашим.
+    pub(crate) fn try_push_node(&self, new_node: *mut Node<T>) -> bool {
+        try_push_to::<T, R>(&self.head, new_node)
+    }
+
+    /// Одна попытка снять верхний элемент: `Ok(None)` — стек пуст,
+    /// `Err(())` — проиграли гонку за head.
+    pub(crate) fn try_pop(&self, guard: &mut R::Guard) -> Result<Option<T>, ()> {
+        let Some(node) = try_pop_from::<T, R>(guard, &self.head)? else {
+            return Ok(None); // Стек пуст
+        };
+
+        // Узел отцеплен только нами: забираем значение, а память
+        // отдаём схеме освобождения — её освободят, когда узел никому не виден
+        Ok(Some(unsafe { self.take_and_retire(guard, node) }))
+    }
+
+    /// Забирает значение снятого нами узла и отдаёт узел схеме освобождения.
+    ///
+    /// # Safety
+    ///
+    /// Узел снят с head нами и больше нигде не опубликован.
+    unsafe fn take_and_retire(&self, guard: &R::Guard, node: *mut Node<T>) -> T {
+        match self.clone_on_pop {
+            // peek/iter могут ещё смотреть на значение в узле: отдаём клон,
+            // а само значение дропнется, когда узел никому не виден
+            Some(clone) => {
+                let value = clone(&(*node).value);
+                R::retire(&self.pool, &self.free, guard, node, true);
+                value
+            }
+            None => {
+                let value = Node::take_value(node);
+                R::retire(&self.pool, &self.free, guard, node, false);
+                value
+            }
+        }
+    }
+}
+
+/// Одна попытка положить узел на вершину `top` (head или список свободных).
+/// false — вершина поменялась, узел остаётся нашим.
+fn try_push_to<T, R: StackReclaimer>(top: &R::Top<T>, node: *mut Node<T>) -> bool {
+    let head = top.load(Ordering::Acquire); // Загружаем текущий верхний элемент
+    unsafe { (*node).next.store(head.node(), Ordering::Relaxed) }; // Новый узел указывает на старый head
+
+    // Атомарно обновляем вершину, если никто другой её не изменил
+    top.compare_exchange_weak(
+        head,
+        head.successor(node),
+        Ordering::Release,
+        Ordering::Relaxed,
+    )
+    .is_ok()
+}
+
+/// Одна попытка снять узел с вершины `top`: `Ok(None)` — пусто,
+/// `Err(())` — проиграли гонку за вершину.
+fn try_pop_from<T, R: StackReclaimer>(
+    guard: &mut R::Guard,
+    top: &R::Top<T>,
+) -> Result<Option<*mut Node<T>>, ()> {
+    let head = R::protect(guard, top); // Загружаем и защищаем текущий head
+    let node = head.node();
+    if node.is_null() {
+        return Ok(None);
+    }
+
+    let next = unsafe { (*node).next.load(Ordering::Relaxed) }; // Узел защищён — читать безопасно
+
+    // Атомарно обновляем вершину, если никто другой её не изменил
+    top.compare_exchange_weak(
+        head,
+        head.successor(next),
+        Ordering::Release,
+        Ordering::Relaxed,
+    )
+    .map(|_| Some(node))
+    .map_err(|_| ())
+}
+
+/// Отдаёт снятый узел схеме `R`: освобождение или возврат в пул.
+/// `drop_value` — значение осталось в узле и дропается перед этим.
+///
+/// # Safety
+///
+/// Как у [`Reclaimer::retire`]; без `drop_value` значение из узла уже забрано.
+unsafe fn retire_node<T, R: Reclaimer>(
+    pool: &Option<Arc<NodePool>>,
+    guard: &R::Guard,
+    node: *mut Node<T>,
+    drop_value: bool,
+) {
+    match pool {
+        Some(pool) => {
+            let pool = Arc::clone(pool);
+            R::retire_with(guard, node, move |p| unsafe {
+                if drop_value {
+                    ManuallyDrop::drop(&mut (*p).value);
+                }
+                pool.recycle(p)
+            });
+        }
+        None if drop_value => R::retire_with(guard, node, |p| unsafe {
+            ManuallyDrop::drop(&mut Box::from_raw(p).value)
+        }),
+        None => R::retire(guard, node),
+    }
+}
+
+impl<T, R: StackReclaimer> Drop for TreiberStack<T, R> {
+    /// Освобождает всю память при уничтожении стека
+    fn drop(&mut self) {
+        // &mut self: других потоков нет, освобождаем узлы напрямую
+        // (память узлов из пула совместима с Box<Node<T>>)
+        let mut cur = self.head.node_mut();
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            cur = *node.next.get_mut();
+            unsafe { ManuallyDrop::drop(&mut node.value) };
+        }
+
+        // Свободные узлы (только у Tagged) — без значений
+        let mut cur = self.free.node_mut();
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur as *mut MaybeUninit<Node<T>>) };
+            cur = *unsafe { node.assume_init_mut() }.next.get_mut();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reclaim::{CrossbeamEpoch, Hazard};
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_single_thread() {
+        let stack = TreiberStack::new();
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None); // Стек пуст
+    }
+
+    #[test]
+    fn test_multi_threaded() {
+        let stack = Arc::new(TreiberStack::new());
+        let mut handles = vec![];
+        let threads = 4;
+        let iterations = 10;
+
+        for i in 0..threads {
+            let stack_clone = Arc::clone(&stack);
+            let handle = thread::spawn(move || {
+                for j in 0..iterations {
+                    stack_clone.push(i * 10 + j);
+                }
+            });
+            handles.push(handle);
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let mut values = vec![];
+        while let Some(value) = stack.pop() {
+            values.push(value);
+        }
+
+        // Проверяем, что все 40 элементов добавились (4 потока * 10 элементов)
+        assert_eq!(values.len(), threads * iterations);
+    }
+
+    /// Значение, считающее свои дропы.
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Каждое значение дропнуто ровно один раз — для любой схемы освобождения.
+    fn concurrent_push_pop<R: Reclaimer>(stack: TreiberStack<DropCounter, R>) {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let stack = Arc::new(stack);
+        let threads = 4;
+        let iterations = 1000;
+
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let (stack, drops) = (Arc::clone(&stack), Arc::clone(&drops));
+                thread::spawn(move || {
+                    for _ in 0..iterations {
+                        stack.push(DropCounter(Arc::clone(&drops)));
+                        stack.pop();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert!(stack.pop().is_none());
+        assert_eq!(drops.load(Ordering::Relaxed), threads * iterations);
+    }
+
+    #[test]
+    fn test_concurrent_push_pop_hazard() {
+        concurrent_push_pop::<Hazard>(TreiberStack::default());
+    }
+
+    #[test]
+    fn test_concurrent_push_pop_ebr() {
+        concurrent_push_pop::<Ebr>(TreiberStack::default());
+    }
+
+    #[test]
+    fn test_concurrent_push_pop_crossbeam() {
+        concurrent_push_pop::<CrossbeamEpoch>(TreiberStack::default());
+    }
+
+    #[test]
+    fn test_pooled_concurrent_push_pop() {
+        let builder = TreiberStack::builder().thread_cache(16).max_pooled(64);
+        concurrent_push_pop(builder.build());
+        concurrent_push_pop(builder.reclaimer::<Hazard>().build());
+        concurrent_push_pop(builder.reclaimer::<CrossbeamEpoch>().build());
+    }
+
+    /// Снятые узлы возвращаются в пул и переиспользуются следующими push.
+    #[test]
+    fn test_pool_reuses_nodes() {
+        let stack = TreiberStack::<u64>::builder()
+            .reclaimer::<Hazard>()
+            .thread_cache(8)
+            .build();
+        assert_eq!(stack.pooled(), 0);
+
+        stack.push_list(0..4);
+        while stack.pop().is_some() {}
+        // Узлы никто не защищает — скан возвращает их в пул
+        crate::hazard::scan();
+        assert_eq!(stack.pooled(), 4);
+
+        stack.push(10);
+        stack.push(11);
+        assert_eq!(stack.pooled(), 2);
+        assert_eq!(stack.pop_all().collect::<Vec<_>>(), vec![11, 10]);
+        crate::hazard::scan();
+        assert_eq!(stack.pooled(), 4);
+    }
+
+    #[test]
+    fn test_pooled_drop_frees_remaining_values() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let stack = TreiberStack::builder().thread_cache(4).build();
+        for _ in 0..10 {
+            stack.push(DropCounter(Arc::clone(&drops)));
+        }
+        drop(stack.pop());
+        drop(stack);
+        assert_eq!(drops.load(Ordering::Relaxed), 10);
+    }
+
+    #[test]
+    fn test_drop_frees_remaining_values() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        let stack = TreiberStack::new();
+        for _ in 0..10 {
+            stack.push(DropCounter(Arc::clone(&drops)));
+        }
+        drop(stack.pop());
+        drop(stack);
+        assert_eq!(drops.load(Ordering::Relaxed), 10);
+    }
+
+    /// Стресс: потоки чередуют пачки push и pop; каждое значение
+    /// снимается ровно один раз. Под Miri прогон укорочен.
+    #[test]
+    fn test_stress_push_pop() {
+        let threads = if cfg!(miri) { 3 } else { 8 };
+        let rounds = if cfg!(miri) { 20 } else { 2000 };
+        let batch = 4;
+        let stack = Arc::new(TreiberStack::new());
+
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for r in 0..rounds {
+                        for b in 0..batch {
+                            stack.push((t * rounds + r) * batch + b);
+                        }
+                        for _ in 0..batch {
+                            popped.extend(stack.pop());
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut values: Vec<_> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        while let Some(v) = stack.pop() {
+            values.push(v);
+        }
+        values.sort_unstable();
+        assert_eq!(values, (0..threads * rounds * batch).collect::<Vec<_>>());
+    }
+}
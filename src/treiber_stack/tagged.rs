@@ -0,0 +1,209 @@
+use std::{
+    mem::{ManuallyDrop, MaybeUninit},
+    ptr,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Arc,
+    },
+};
+
+use super::sealed::{self, AtomicTop, TopWord};
+use super::{try_pop_from, try_push_to, Node, TreiberStack};
+use crate::pool::NodePool;
+use crate::tagged::{AtomicTaggedPtr, TaggedPtr};
+
+/// Стек без схемы освобождения памяти: `TreiberStack<T, Tagged>`.
+///
+/// Узлы не возвращаются аллокатору, пока жив стек, а переиспользуются
+/// через внутренний список свободных, поэтому разыменовать снятый узел
+/// безопасно и закрепляться не нужно. Зато адрес узла возвращается
+/// на вершину — это ровно ABA-сценарий, от которого защищает версия
+/// в head и списке свободных ([`AtomicTaggedPtr`]): каждый успешный CAS
+/// на вершину увеличивает версию, и устаревший снимок не пройдёт.
+///
+/// Подходит для горячих путей, где аллокация на каждый push дорога,
+/// а память стека может оставаться на пиковом уровне. Пул узлов билдера
+/// и [`pop_all`](TreiberStack::pop_all) — только для схем освобождения.
+pub struct Tagged;
+
+impl<N> TopWord<N> for TaggedPtr<N> {
+    fn node(self) -> *mut N {
+        self.ptr()
+    }
+
+    fn successor(self, node: *mut N) -> Self {
+        TaggedPtr::successor(self, node)
+    }
+}
+
+impl<N> AtomicTop<N> for AtomicTaggedPtr<N> {
+    type Word = TaggedPtr<N>;
+
+    fn null() -> Self {
+        AtomicTaggedPtr::default()
+    }
+
+    fn load(&self, ord: Ordering) -> TaggedPtr<N> {
+        AtomicTaggedPtr::load(self, ord)
+    }
+
+    fn compare_exchange_weak(
+        &self,
+        current: TaggedPtr<N>,
+        new: TaggedPtr<N>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<TaggedPtr<N>, TaggedPtr<N>> {
+        AtomicTaggedPtr::compare_exchange_weak(self, current, new, success, failure)
+    }
+
+    fn node_mut(&mut self) -> *mut N {
+        self.get_mut().ptr()
+    }
+}
+
+impl sealed::StackReclaimer for Tagged {
+    type Guard = ();
+    type Top<T> = AtomicTaggedPtr<Node<T>>;
+
+    fn pin() {}
+
+    fn protect<T>(_guard: &mut (), head: &AtomicTaggedPtr<Node<T>>) -> TaggedPtr<Node<T>> {
+        // Память узла жива до дропа стека, устаревший снимок отсечёт версия
+        head.load(Ordering::Acquire)
+    }
+
+    /// Узел из списка свободных, а если он пуст — новый.
+    fn alloc<T>(
+        _pool: &Option<Arc<NodePool>>,
+        free: &AtomicTaggedPtr<Node<T>>,
+        value: T,
+    ) -> *mut Node<T> {
+        loop {
+            match try_pop_from::<T, Tagged>(&mut (), free) {
+                Ok(Some(node)) => {
+                    // Узел наш: value никто не читает, пока он не на вершине,
+                    // а next устаревшие pop читают атомарно
+                    unsafe { ptr::addr_of_mut!((*node).value).write(ManuallyDrop::new(value)) };
+                    return node;
+                }
+                Ok(None) => return Node::alloc(value),
+                Err(()) => {}
+            }
+        }
+    }
+
+    unsafe fn retire<T>(
+        _pool: &Option<Arc<NodePool>>,
+        free: &AtomicTaggedPtr<Node<T>>,
+        _guard: &(),
+        node: *mut Node<T>,
+        drop_value: bool,
+    ) {
+        if drop_value {
+            // Значение здесь читает только снявший поток: peek у Tagged нет
+            ManuallyDrop::drop(&mut *ptr::addr_of_mut!((*node).value));
+        }
+        while !try_push_to::<T, Tagged>(free, node) {}
+    }
+}
+
+impl<T> TreiberStack<T, Tagged> {
+    /// Создаёт пустой стек с `n` заранее выделенными узлами.
+    pub fn with_capacity(n: usize) -> Self {
+        let stack = Self::default();
+        for _ in 0..n {
+            let node = Box::into_raw(Box::new(MaybeUninit::<Node<T>>::uninit())) as *mut Node<T>;
+            // Значение не инициализируем: свободный узел его не содержит
+            unsafe { ptr::addr_of_mut!((*node).next).write(AtomicPtr::new(ptr::null_mut())) };
+            while !try_push_to::<T, Tagged>(&stack.free, node) {}
+        }
+        stack
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    #[test]
+    fn test_nodes_are_recycled() {
+        let stack = TreiberStack::<_, Tagged>::with_capacity(2);
+        assert_eq!(stack.pooled(), 2);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3); // пул исчерпан — новый узел
+        assert_eq!(stack.pooled(), 0);
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pooled(), 2);
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+        assert_eq!(stack.pooled(), 3);
+
+        // Пачка тоже берёт узлы из списка свободных
+        stack.push_list([4, 5]);
+        assert_eq!(stack.pooled(), 1);
+        assert_eq!(stack.pop(), Some(5));
+        assert_eq!(stack.pop(), Some(4));
+    }
+
+    #[test]
+    fn test_drop_drops_remaining_values() {
+        struct DropCounter(Arc<AtomicUsize>);
+
+        impl Drop for DropCounter {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let stack = TreiberStack::<_, Tagged>::default();
+        for _ in 0..4 {
+            stack.push(DropCounter(drops.clone()));
+        }
+        drop(stack.pop());
+        drop(stack);
+        assert_eq!(drops.load(Ordering::Relaxed), 4);
+    }
+
+    /// Узлы постоянно возвращаются на вершину под теми же адресами:
+    /// без версии в head такой прогон ловит ABA (потерянные или
+    /// задвоенные значения).
+    #[test]
+    fn test_concurrent_recycling() {
+        let threads = if cfg!(miri) { 3 } else { 8 };
+        let iterations = if cfg!(miri) { 50 } else { 5000 };
+        let stack = Arc::new(TreiberStack::<_, Tagged>::with_capacity(threads));
+
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for i in 0..iterations {
+                        stack.push(t * iterations + i);
+                        popped.extend(stack.pop());
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut values: Vec<_> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        while let Some(v) = stack.pop() {
+            values.push(v);
+        }
+        values.sort_unstable();
+        assert_eq!(values, (0..threads * iterations).collect::<Vec<_>>());
+    }
+}
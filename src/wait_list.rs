@@ -0,0 +1,175 @@
+//! Список ждущих потребителей для блокирующих и async-операций.
+//!
+//! Lock-free структура сама ждать не умеет: pop на пустой очереди просто
+//! возвращает `None`. [`WaitList`] добавляет ожидание сбоку, не трогая
+//! операции самой структуры: push после вставки вызывает
+//! [`WaitList::notify_if_waiting`], который читает один счётчик и идёт
+//! в мьютекс, только если кто-то ждёт.
+//!
+//! Протокол без потерянных пробуждений (как у Деккера, пара заборов):
+//! - потребитель регистрируется (счётчик растёт), ставит `fence(SeqCst)`
+//!   и ещё раз пробует pop — и только потом засыпает;
+//! - производитель публикует элемент обычной Release-операцией, ставит
+//!   `fence(SeqCst)` и только потом читает счётчик.
+//!
+//! Либо производитель увидит ждущего, либо повторный pop увидит элемент.
+//! Забор производителя нельзя перенести за проверку счётчика: без него
+//! чтение счётчика может обогнать публикацию, и обе стороны разминутся.
+//!
+//! Разбуженный ждущий удаляется из списка. Если ждущий уходит, не проснувшись
+//! по этому пробуждению (повторный pop забрал другой элемент, таймаут,
+//! отмена future), [`WaitList::unregister`] передаёт пробуждение следующему:
+//! элемент, ради которого будили, мог остаться в структуре.
+
+use std::{
+    collections::VecDeque,
+    sync::atomic::{self, AtomicUsize, Ordering},
+    task::Waker,
+    thread::{self, Thread},
+    time::Instant,
+};
+
+use parking_lot::Mutex;
+
+/// Как будить ждущего.
+enum Wake {
+    Thread(Thread),
+    Task(Waker),
+}
+
+impl Wake {
+    fn wake(self) {
+        match self {
+            Wake::Thread(thread) => thread.unpark(),
+            Wake::Task(waker) => waker.wake(),
+        }
+    }
+}
+
+/// Номер записи в списке ждущих.
+pub(crate) type WaitId = u64;
+
+#[derive(Default)]
+struct Inner {
+    next_id: WaitId,
+    waiters: VecDeque<(WaitId, Wake)>,
+}
+
+/// Список ждущих (FIFO).
+#[derive(Default)]
+pub(crate) struct WaitList {
+    /// Сколько записей в списке; читается производителем без мьютекса.
+    waiting: AtomicUsize,
+    inner: Mutex<Inner>,
+}
+
+impl WaitList {
+    /// Есть ли ждущие (без забора — для проверок и статистики).
+    pub(crate) fn has_waiters(&self) -> bool {
+        self.len() != 0
+    }
+
+    /// Будит первого ждущего, если он есть. Вызывать сразу после
+    /// публикации элемента; забор здесь — пара к забору в регистрации.
+    pub(crate) fn notify_if_waiting(&self) {
+        atomic::fence(Ordering::SeqCst);
+        if self.has_waiters() {
+            self.notify_one();
+        }
+    }
+
+    /// Сколько сейчас ждущих.
+    pub(crate) fn len(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
+    }
+
+    /// Будит первого ждущего, если он есть.
+    pub(crate) fn notify_one(&self) {
+        let wake = {
+            let mut inner = self.inner.lock();
+            let wake = inner.waiters.pop_front().map(|(_, wake)| wake);
+            if wake.is_some() {
+                self.waiting.fetch_sub(1, Ordering::SeqCst);
+            }
+            wake
+        };
+        // Будим вне мьютекса
+        if let Some(wake) = wake {
+            wake.wake();
+        }
+    }
+
+    /// Регистрирует текущий поток. После возврата нужно ещё раз проверить
+    /// структуру и только затем парковаться.
+    pub(crate) fn register_thread(&self) -> WaitId {
+        self.register(Wake::Thread(thread::current()))
+    }
+
+    /// Регистрирует задачу или обновляет её waker, если `id` ещё в списке.
+    /// Возвращает `None`, если задачу уже разбудили (запись удалена).
+    pub(crate) fn register_task(&self, id: Option<WaitId>, waker: &Waker) -> Option<WaitId> {
+        let Some(id) = id else {
+            return Some(self.register(Wake::Task(waker.clone())));
+        };
+        let mut inner = self.inner.lock();
+        let entry = inner.waiters.iter_mut().find(|(i, _)| *i == id)?;
+        match &mut entry.1 {
+            Wake::Task(old) if old.will_wake(waker) => {}
+            wake => *wake = Wake::Task(waker.clone()),
+        }
+        Some(id)
+    }
+
+    fn register(&self, wake: Wake) -> WaitId {
+        let id = {
+            let mut inner = self.inner.lock();
+            let id = inner.next_id;
+            inner.next_id += 1;
+            inner.waiters.push_back((id, wake));
+            self.waiting.fetch_add(1, Ordering::SeqCst);
+            id
+        };
+        // Пара к забору производителя в notify_if_waiting
+        atomic::fence(Ordering::SeqCst);
+        id
+    }
+
+    /// Всё ещё ли `id` ждёт (не разбужен).
+    pub(crate) fn is_waiting(&self, id: WaitId) -> bool {
+        self.inner.lock().waiters.iter().any(|(i, _)| *i == id)
+    }
+
+    /// Снимает регистрацию. Если запись уже удалена (ждущего разбудили),
+    /// а пробуждение не понадобилось, передаёт его следующему.
+    pub(crate) fn unregister(&self, id: WaitId) {
+        let mut inner = self.inner.lock();
+        match inner.waiters.iter().position(|(i, _)| *i == id) {
+            Some(pos) => {
+                inner.waiters.remove(pos);
+                self.waiting.fetch_sub(1, Ordering::SeqCst);
+            }
+            None => {
+                drop(inner);
+                self.notify_one();
+            }
+        }
+    }
+
+    /// Паркует текущий поток, пока `id` не разбудят или не наступит `deadline`.
+    /// Возвращает `false` по таймауту.
+    pub(crate) fn park(&self, id: WaitId, deadline: Option<Instant>) -> bool {
+        while self.is_waiting(id) {
+            match deadline {
+                None => thread::park(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    thread::park_timeout(deadline - now);
+                }
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,8 @@
+//! Проверки на этапе компиляции: неправильное использование структур
+//! между потоками (и небезопасное чтение без снятия) не должно собираться.
+
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,11 @@
+use std::thread;
+
+use rust_lockfree::ebr;
+
+// Guard привязан к участнику текущего потока.
+fn main() {
+    let guard = ebr::pin();
+    thread::spawn(move || {
+        drop(guard);
+    });
+}
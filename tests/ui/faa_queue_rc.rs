@@ -0,0 +1,12 @@
+use std::rc::Rc;
+use std::thread;
+
+use rust_lockfree::faa_queue::FaaQueue;
+
+// Rc нельзя передавать между потоками — и очередь с ним тоже.
+fn main() {
+    let queue = FaaQueue::<Rc<i32>>::new();
+    thread::spawn(move || {
+        queue.pop();
+    });
+}
@@ -0,0 +1,12 @@
+use std::rc::Rc;
+use std::thread;
+
+use rust_lockfree::ms_queue_crossbeam::MSQueue;
+
+// Rc нельзя передавать между потоками — и очередь с ним тоже.
+fn main() {
+    let queue = MSQueue::<Rc<i32>>::new();
+    thread::spawn(move || {
+        queue.pop();
+    });
+}
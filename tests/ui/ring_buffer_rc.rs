@@ -0,0 +1,13 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use std::thread;
+
+use rust_lockfree::ring_buffer::RingBuffer;
+
+// Rc нельзя передавать между потоками — и буфер с ним тоже.
+fn main() {
+    let buffer = Arc::new(RingBuffer::<Rc<i32>>::new(4));
+    thread::spawn(move || {
+        let _ = buffer.pop();
+    });
+}
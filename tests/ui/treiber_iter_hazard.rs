@@ -0,0 +1,11 @@
+use rust_lockfree::reclaim::{Hazard, Reclaimer};
+use rust_lockfree::treiber_stack::TreiberStack;
+
+// Hazard pointers защищают лишь пару узлов — обход цепочки под ними небезопасен.
+fn main() {
+    let stack = TreiberStack::<u32>::builder()
+        .reclaimer::<Hazard>()
+        .peekable();
+    let guard = Hazard::pin();
+    let _ = stack.iter(&guard).count();
+}
@@ -0,0 +1,10 @@
+use rust_lockfree::reclaim::{Ebr, Reclaimer};
+use rust_lockfree::treiber_stack::TreiberStack;
+
+// peek/iter есть только у стека из builder().peekable(): обычный pop
+// забирает значение из узла, и ссылка на него повисла бы.
+fn main() {
+    let stack: TreiberStack<u32> = TreiberStack::new();
+    let guard = Ebr::pin();
+    let _ = stack.iter(&guard).count();
+}
@@ -0,0 +1,11 @@
+use std::cell::Cell;
+
+use rust_lockfree::reclaim::{Ebr, Reclaimer};
+use rust_lockfree::treiber_stack::TreiberStack;
+
+// Одно значение через peek читают несколько потоков — Cell так разделять нельзя.
+fn main() {
+    let stack = TreiberStack::<Cell<u32>>::builder().peekable();
+    let guard = Ebr::pin();
+    let _ = stack.peek(&guard);
+}
@@ -0,0 +1,13 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use std::thread;
+
+use rust_lockfree::treiber_stack::TreiberStack;
+
+// Rc нельзя передавать между потоками — и стек с ним тоже.
+fn main() {
+    let stack = Arc::new(TreiberStack::<Rc<i32>>::new());
+    thread::spawn(move || {
+        stack.pop();
+    });
+}